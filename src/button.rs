@@ -0,0 +1,259 @@
+use core::time::Duration;
+
+use crate::{error::Error, lock::BusLock, Input};
+
+/// The level an input reads while its button is pressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Active {
+    /// Pressed reads low, such as a button to ground with a pull-up.
+    Low,
+    /// Pressed reads high, such as a button to the supply with a pull-down.
+    High,
+}
+
+/// Something that happened to a [`Button`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonEvent {
+    /// The button was pressed.
+    Pressed,
+    /// The button was released.
+    Released,
+    /// The button has been held for the long press time. Reported once per
+    /// press.
+    LongPress,
+    /// The button was released before the long press time. Reported right
+    /// after [`Released`](Self::Released).
+    Click,
+}
+
+/// A debounced button on an expander input, reporting presses, releases,
+/// long presses and clicks.
+///
+/// Time is supplied by the caller as a monotonic timestamp, from any epoch,
+/// so this works with whatever time source the application has. Inputs with
+/// hardware debounce can set the software debounce time to zero.
+///
+/// ```rust
+/// # use sx1509_eh::{button::{Active, Button}, Pins, Sx1509};
+/// # use core::time::Duration;
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, now: impl Fn() -> Duration) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { a0, .. } = expander.split();
+/// let mut button = Button::new(a0.into_input()?.pullup()?, Active::Low)
+///     .long_press(Duration::from_secs(1));
+///
+/// loop {
+///     for event in button.update(now())? {
+///         // ...
+///     }
+/// }
+/// # }
+/// ```
+pub struct Button<'a, const PIN: u8, I2C: BusLock, S, D, N> {
+    input: Input<'a, PIN, I2C, S, D, N>,
+    active: Active,
+    debounce: Duration,
+    long_press: Duration,
+    /// The debounced state.
+    pressed: bool,
+    /// The raw state, and when it last changed.
+    raw: bool,
+    raw_since: Duration,
+    pressed_at: Duration,
+    long_pressed: bool,
+}
+
+impl<'a, const PIN: u8, I2C: BusLock, S, D, N> Button<'a, PIN, I2C, S, D, N> {
+    /// Create a button on `input`, which reads `active` while pressed. It
+    /// starts off released, with a 20ms debounce time and a 500ms long press
+    /// time.
+    #[must_use]
+    pub fn new(input: Input<'a, PIN, I2C, S, D, N>, active: Active) -> Self {
+        Self {
+            input,
+            active,
+            debounce: Duration::from_millis(20),
+            long_press: Duration::from_millis(500),
+            pressed: false,
+            raw: false,
+            raw_since: Duration::ZERO,
+            pressed_at: Duration::ZERO,
+            long_pressed: false,
+        }
+    }
+
+    /// Only accept a change once the input has been stable for `time`.
+    #[must_use]
+    pub fn debounce(mut self, time: Duration) -> Self {
+        self.debounce = time;
+        self
+    }
+
+    /// Report a [`ButtonEvent::LongPress`] once the button has been held for
+    /// `time`.
+    #[must_use]
+    pub fn long_press(mut self, time: Duration) -> Self {
+        self.long_press = time;
+        self
+    }
+
+    /// Whether the button is pressed, after debouncing.
+    #[must_use]
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Take the input back.
+    #[must_use]
+    pub fn release(self) -> Input<'a, PIN, I2C, S, D, N> {
+        self.input
+    }
+}
+
+impl<const PIN: u8, I2C, E, S, D, N> Button<'_, PIN, I2C, S, D, N>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Read the input at time `now` and report what happened. Call this
+    /// regularly, at least a few times per debounce time, so that presses
+    /// and long presses are noticed.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn update(&mut self, now: Duration) -> Result<ButtonEvents, Error<E>> {
+        let raw = self.input.interface.get_data::<PIN>()? == (self.active == Active::High);
+        if raw != self.raw {
+            self.raw = raw;
+            self.raw_since = now;
+        }
+
+        let mut events = ButtonEvents::default();
+        if self.raw != self.pressed && now.saturating_sub(self.raw_since) >= self.debounce {
+            self.pressed = self.raw;
+            if self.pressed {
+                self.pressed_at = now;
+                self.long_pressed = false;
+                events.push(ButtonEvent::Pressed);
+            } else {
+                events.push(ButtonEvent::Released);
+                if !self.long_pressed {
+                    events.push(ButtonEvent::Click);
+                }
+            }
+        }
+
+        if self.pressed
+            && !self.long_pressed
+            && now.saturating_sub(self.pressed_at) >= self.long_press
+        {
+            self.long_pressed = true;
+            events.push(ButtonEvent::LongPress);
+        }
+
+        Ok(events)
+    }
+}
+
+/// The events from a [`Button::update`], in the order they happened.
+#[derive(Debug, Default, Clone)]
+pub struct ButtonEvents {
+    events: [Option<ButtonEvent>; 2],
+    index: usize,
+}
+
+impl ButtonEvents {
+    fn push(&mut self, event: ButtonEvent) {
+        if let Some(slot) = self.events.iter_mut().find(|slot| slot.is_none()) {
+            *slot = Some(event);
+        }
+    }
+}
+
+impl Iterator for ButtonEvents {
+    type Item = ButtonEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let event = self.events.get_mut(self.index)?.take();
+        self.index += 1;
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{mock::MockBus, reg::Register, Sx1509};
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Drive `a0` the way a button to ground would.
+    fn press(bus: &MockBus, pressed: bool) {
+        let data = bus.register(Register::RegDataA as u8);
+        let data = if pressed { data & !1 } else { data | 1 };
+        bus.set_register(Register::RegDataA as u8, data);
+    }
+
+    #[test]
+    fn short_press_clicks() {
+        let bus = MockBus::new();
+        let mut expander = Sx1509::new(bus.clone(), 0x3E).unwrap();
+        let input = expander.split().a0.into_input().unwrap();
+        let mut button = Button::new(input, Active::Low);
+        let mut update = |now| button.update(ms(now)).unwrap().collect::<Vec<_>>();
+
+        press(&bus, true);
+        assert_eq!(update(0), []);
+        // Still bouncing.
+        assert_eq!(update(10), []);
+        assert_eq!(update(20), [ButtonEvent::Pressed]);
+
+        press(&bus, false);
+        assert_eq!(update(100), []);
+        assert_eq!(update(120), [ButtonEvent::Released, ButtonEvent::Click]);
+    }
+
+    #[test]
+    fn bounce_is_ignored() {
+        let bus = MockBus::new();
+        let mut expander = Sx1509::new(bus.clone(), 0x3E).unwrap();
+        let input = expander.split().a0.into_input().unwrap();
+        let mut button = Button::new(input, Active::Low);
+
+        press(&bus, true);
+        assert_eq!(button.update(ms(0)).unwrap().count(), 0);
+        press(&bus, false);
+        assert_eq!(button.update(ms(30)).unwrap().count(), 0);
+        assert!(!button.is_pressed());
+    }
+
+    #[test]
+    fn long_press_replaces_the_click() {
+        let bus = MockBus::new();
+        let mut expander = Sx1509::new(bus.clone(), 0x3E).unwrap();
+        let input = expander.split().a0.into_input().unwrap();
+        let mut button = Button::new(input, Active::Low).long_press(ms(1000));
+        let mut update = |now| button.update(ms(now)).unwrap().collect::<Vec<_>>();
+
+        press(&bus, true);
+        update(0);
+        assert_eq!(update(20), [ButtonEvent::Pressed]);
+        assert_eq!(update(1000), []);
+        assert_eq!(update(1020), [ButtonEvent::LongPress]);
+        // Only once per press.
+        assert_eq!(update(3000), []);
+
+        press(&bus, false);
+        update(3000);
+        assert_eq!(update(3020), [ButtonEvent::Released]);
+    }
+}
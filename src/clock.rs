@@ -0,0 +1,285 @@
+use embedded_hal::digital::{ErrorType, OutputPin, StatefulOutputPin};
+
+use crate::{error::Error, lock::BusLock, Interface};
+
+/// The frequency of the internal oscillator.
+pub const INTERNAL_OSCILLATOR_HZ: u32 = 2_000_000;
+
+/// Where the chip's clock `fOSC` comes from. Debouncing, the keypad engine and
+/// the LED driver all run from it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClockSource {
+    /// No clock, for the lowest power. Debouncing, the keypad engine and the
+    /// LED driver stop.
+    Off,
+    /// An external clock of the given frequency in Hz on the OSCIO pin, which
+    /// must not exceed 2MHz. The frequency can't be measured by the chip, so
+    /// it is only used to convert register values into durations.
+    External(u32),
+    /// The internal 2MHz oscillator.
+    #[default]
+    Internal,
+}
+
+impl ClockSource {
+    /// The frequency of the clock in Hz, or `0` if it is off.
+    #[must_use]
+    pub const fn hz(self) -> u32 {
+        match self {
+            ClockSource::Off => 0,
+            ClockSource::External(hz) => hz,
+            ClockSource::Internal => INTERNAL_OSCILLATOR_HZ,
+        }
+    }
+
+    /// The `OscSrc` bits of `RegClock`.
+    pub(crate) const fn bits(self) -> u8 {
+        match self {
+            ClockSource::Off => 0b00,
+            ClockSource::External(_) => 0b01,
+            ClockSource::Internal => 0b10,
+        }
+    }
+}
+
+/// What the OSCIO pin does, when it isn't the
+/// [external clock input](ClockSource::External).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OscioFunction {
+    /// An input, which is unused unless the clock is external.
+    #[default]
+    Input,
+    /// Drive `fOSC` divided by `2^n` out of the pin, where `n` is below 14.
+    ClockOutput(u8),
+    /// Drive the pin low.
+    Low,
+    /// Drive the pin high.
+    High,
+}
+
+impl OscioFunction {
+    /// The `OscioFunction` and `OscOutFreq` bits of `RegClock`.
+    const fn bits(self) -> u8 {
+        match self {
+            OscioFunction::Input => 0b0_0000,
+            OscioFunction::ClockOutput(divider) => 0b1_0000 | (divider + 1),
+            OscioFunction::Low => 0b1_0000,
+            OscioFunction::High => 0b1_1111,
+        }
+    }
+}
+
+/// How the chip is clocked. Build one with [`new`](Self::new) and pass it to
+/// [`Sx1509::new_with_clock`](crate::Sx1509::new_with_clock).
+///
+/// ```rust
+/// # use sx1509_eh::{clock::{ClockConfig, ClockSource}, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// // Never debounces, scans a keypad or drives LEDs, so save the power.
+/// let expander = Sx1509::new_with_clock(i2c, 0x3E, ClockConfig::new().source(ClockSource::Off))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockConfig {
+    source: ClockSource,
+    oscio: OscioFunction,
+    led_divider: u8,
+}
+
+impl ClockConfig {
+    /// The internal oscillator, with OSCIO as an input and the LED driver
+    /// clock off until an LED needs it.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            source: ClockSource::Internal,
+            oscio: OscioFunction::Input,
+            led_divider: 0,
+        }
+    }
+
+    /// Where the clock comes from.
+    #[must_use]
+    pub const fn source(mut self, source: ClockSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// What OSCIO does. This is ignored with an
+    /// [external clock](ClockSource::External), which needs OSCIO as its
+    /// input.
+    ///
+    /// # Panics
+    /// This function will panic if a [clock output](OscioFunction::ClockOutput)
+    /// divider is not below 14.
+    #[must_use]
+    pub const fn oscio(mut self, oscio: OscioFunction) -> Self {
+        if let OscioFunction::ClockOutput(divider) = oscio {
+            assert!(divider < 14, "the clock output divider must be below 14");
+        }
+        self.oscio = oscio;
+        self
+    }
+
+    /// Run the LED driver clock at `fOSC` divided by `2^(divider - 1)`, or
+    /// turn it off with `0`.
+    ///
+    /// # Panics
+    /// This function will panic if `divider` is above 7.
+    #[must_use]
+    pub const fn led_divider(mut self, divider: u8) -> Self {
+        assert!(divider <= 7, "the LED clock divider must be at most 7");
+        self.led_divider = divider;
+        self
+    }
+
+    /// The frequency of the clock in Hz, or `0` if it is off.
+    #[must_use]
+    pub const fn hz(&self) -> u32 {
+        self.source.hz()
+    }
+
+    /// Where the clock comes from.
+    #[must_use]
+    pub const fn clock_source(&self) -> ClockSource {
+        self.source
+    }
+
+    /// What OSCIO does.
+    #[must_use]
+    pub const fn oscio_function(&self) -> OscioFunction {
+        self.oscio
+    }
+
+    /// The LED driver clock divider, or `0` if it is off.
+    #[must_use]
+    pub const fn led_clock_divider(&self) -> u8 {
+        self.led_divider
+    }
+
+    /// Decode `RegClock` and `RegMisc`. The chip can't tell the frequency of
+    /// an external clock, so it is taken from `external_hz`.
+    pub(crate) const fn from_bits(clock: u8, misc: u8, external_hz: u32) -> Self {
+        let source = match (clock >> 5) & 0b11 {
+            0b00 => ClockSource::Off,
+            0b01 => ClockSource::External(external_hz),
+            _ => ClockSource::Internal,
+        };
+        let oscio = match (clock >> 4 & 1, clock & 0b1111) {
+            (0, _) => OscioFunction::Input,
+            (_, 0b0000) => OscioFunction::Low,
+            (_, 0b1111) => OscioFunction::High,
+            (_, frequency) => OscioFunction::ClockOutput(frequency - 1),
+        };
+        Self {
+            source,
+            oscio,
+            led_divider: (misc >> 4) & 0b111,
+        }
+    }
+
+    /// The value of `RegClock`.
+    pub(crate) const fn clock_bits(&self) -> u8 {
+        let oscio = match self.source {
+            ClockSource::External(_) => OscioFunction::Input,
+            _ => self.oscio,
+        };
+        self.source.bits() << 5 | oscio.bits()
+    }
+
+    /// The `ClkX` bits of `RegMisc`.
+    pub(crate) const fn misc_bits(&self) -> u8 {
+        self.led_divider << 4
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The OSCIO pin used as a general-purpose output, such as for a status LED.
+/// Setting it makes OSCIO an output, so it can't be used alongside an
+/// [external clock](ClockSource::External) or the
+/// [clock output](crate::Sx1509::set_clock_output).
+pub struct Oscio<'a, I2C: BusLock> {
+    interface: &'a Interface<I2C>,
+}
+
+impl<'a, I2C: BusLock> Oscio<'a, I2C> {
+    pub(crate) fn new(interface: &'a Interface<I2C>) -> Self {
+        Self { interface }
+    }
+}
+
+impl<I2C, E> OutputPin for Oscio<'_, I2C>
+where
+    I2C: BusLock<Error = E>,
+    E: core::fmt::Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.interface.set_oscio(false)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.interface.set_oscio(true)
+    }
+}
+
+impl<I2C, E> StatefulOutputPin for Oscio<'_, I2C>
+where
+    I2C: BusLock<Error = E>,
+    E: core::fmt::Debug,
+{
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        self.interface.get_oscio()
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        self.is_set_high().map(|v| !v)
+    }
+}
+
+impl<I2C, E> ErrorType for Oscio<'_, I2C>
+where
+    I2C: BusLock<Error = E>,
+    E: core::fmt::Debug,
+{
+    type Error = Error<E>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_config_round_trips() {
+        let config = ClockConfig::new()
+            .oscio(OscioFunction::ClockOutput(3))
+            .led_divider(2);
+        assert_eq!(config.clock_bits(), 0b0101_0100);
+        assert_eq!(config.misc_bits(), 0b0010_0000);
+        assert_eq!(
+            ClockConfig::from_bits(config.clock_bits(), config.misc_bits(), 0),
+            config
+        );
+    }
+
+    #[test]
+    fn external_clock_keeps_oscio_an_input() {
+        let config = ClockConfig::new()
+            .source(ClockSource::External(1_000_000))
+            .oscio(OscioFunction::High);
+        assert_eq!(config.clock_bits(), 0b0010_0000);
+        assert_eq!(
+            ClockConfig::from_bits(config.clock_bits(), 0, 1_000_000).clock_source(),
+            ClockSource::External(1_000_000)
+        );
+    }
+}
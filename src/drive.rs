@@ -0,0 +1,57 @@
+/// How fast a pin's output edges are.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Slew {
+    /// Full speed edges, as after reset.
+    #[default]
+    Fast,
+    /// Slow edges, which ring less on long cables and radiate less on
+    /// EMI-sensitive nets.
+    Long,
+}
+
+/// How much current a pin's output can source or sink.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DriveStrength {
+    /// Full drive strength, as after reset.
+    #[default]
+    Full,
+    /// Reduced drive strength, for outputs that would otherwise drive more
+    /// current than needed.
+    Low,
+}
+
+/// The electrical tuning of a pin, applied in one call with
+/// [`Output::set_drive_profile`](crate::Output::set_drive_profile),
+/// [`Input::set_drive_profile`](crate::Input::set_drive_profile) or, for many
+/// pins at once, [`Sx1509::set_drive_profile`](crate::Sx1509::set_drive_profile).
+/// The default is the power-on tuning.
+///
+/// ```rust
+/// # use sx1509_eh::{drive::{DriveProfile, DriveStrength, Slew}, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { a0, .. } = expander.split();
+/// # let mut led = a0.into_output()?;
+/// let profile = DriveProfile {
+///     slew: Slew::Long,
+///     drive_strength: DriveStrength::Low,
+///     high_input: false,
+/// };
+/// led.set_drive_profile(profile)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DriveProfile {
+    /// How fast the output edges are.
+    pub slew: Slew,
+    /// How much current the output can source or sink.
+    pub drive_strength: DriveStrength,
+    /// Whether the input accepts logic levels above its bank's supply, up to
+    /// 5.5V.
+    pub high_input: bool,
+}
@@ -0,0 +1,255 @@
+use crate::{
+    error::{Error, ModeChange},
+    interrupt::Edge,
+    lock::BusLock,
+    Interface, Pin,
+};
+
+/// How many quadrature transitions make up one reported step.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Detent {
+    /// Report every transition, four per full cycle.
+    Off = 1,
+    /// Report a step every two transitions, for encoders with a detent at
+    /// every half cycle.
+    Half = 2,
+    /// Report a step every full cycle of four transitions, for encoders with
+    /// a detent at every full cycle. This is the most common kind.
+    #[default]
+    Full = 4,
+}
+
+/// The change in position for each previous and current `BA` state. Invalid
+/// transitions, where both signals changed at once, count as no movement.
+const TRANSITIONS: [i8; 16] = [0, 1, -1, 0, -1, 0, 0, 1, 1, 0, 0, -1, 0, -1, 1, 0];
+
+/// A quadrature rotary encoder on two expander pins.
+///
+/// Both pins are pulled up and made to assert NINT on every edge, so
+/// [`update`](Self::update) only needs calling when NINT asserts and
+/// [`mask`](Self::mask) is among the triggered pins. Each update reads both
+/// banks in one transaction.
+///
+/// ```rust
+/// # use sx1509_eh::{encoder::RotaryEncoder, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let Pins { a0, a1, .. } = expander.split();
+/// let mut knob = RotaryEncoder::new(a0, a1).unwrap();
+///
+/// // When the MCU sees NINT go low:
+/// let steps = knob.update()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct RotaryEncoder<'a, const A: u8, const B: u8, I2C: BusLock> {
+    interface: &'a Interface<I2C>,
+    a: Pin<'a, A, I2C>,
+    b: Pin<'a, B, I2C>,
+    /// The last `BA` state.
+    state: u8,
+    /// Transitions not yet making up a whole step.
+    remainder: i8,
+    detent: Detent,
+    position: i32,
+}
+
+impl<'a, const A: u8, const B: u8, I2C, E> RotaryEncoder<'a, A, B, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Set up the encoder's `a` and `b` pins as pulled up inputs that
+    /// interrupt on both edges.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the pins can be extracted from the [`ModeChange`],
+    /// although they may be partially configured.
+    ///
+    /// # Panics
+    /// Both pins must belong to the same expander. This function panics if
+    /// they don't.
+    pub fn new(
+        a: Pin<'a, A, I2C>,
+        b: Pin<'a, B, I2C>,
+    ) -> Result<Self, ModeChange<Error<E>, (Pin<'a, A, I2C>, Pin<'a, B, I2C>)>> {
+        assert!(
+            core::ptr::eq(a.interface, b.interface),
+            "the pins of an encoder must belong to the same expander"
+        );
+
+        let interface = a.interface;
+        let result = interface
+            .coalesce(|stage| {
+                stage.set_input::<A>()?;
+                stage.set_input::<B>()?;
+                stage.set_pull_down::<A>(false)?;
+                stage.set_pull_down::<B>(false)?;
+                stage.set_pull_up::<A>(true)?;
+                stage.set_pull_up::<B>(true)?;
+                stage.enable_interrupt::<A>(Edge::Both)?;
+                stage.enable_interrupt::<B>(Edge::Both)?;
+                Ok(())
+            })
+            .and_then(|()| interface.get_data_mask());
+
+        match result {
+            Ok(data) => Ok(Self {
+                interface,
+                a,
+                b,
+                state: Self::decode(data),
+                remainder: 0,
+                detent: Detent::Full,
+                position: 0,
+            }),
+            Err(error) => Err(ModeChange { error, pin: (a, b) }),
+        }
+    }
+
+    /// Stop the pins from interrupting and return them.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the encoder can be extracted from the
+    /// [`ModeChange`].
+    pub fn release(self) -> Result<(Pin<'a, A, I2C>, Pin<'a, B, I2C>), ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.disable_interrupt::<A>()?;
+            stage.disable_interrupt::<B>()?;
+            stage.set_pull_up::<A>(false)?;
+            stage.set_pull_up::<B>(false)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok((self.a, self.b)),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Read the pins and return how many steps the encoder has turned since
+    /// the last update, positive being clockwise (`a` leading `b`). The
+    /// pins' interrupts are cleared.
+    ///
+    /// Only one transition can be seen per update, so this needs to be called
+    /// on every edge to keep up with fast turning.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn update(&mut self) -> Result<i32, Error<E>> {
+        // Clear before reading, so an edge in between interrupts again
+        // rather than being cleared without being decoded.
+        self.interface.clear_interrupts(self.mask())?;
+        let state = Self::decode(self.interface.get_data_mask()?);
+
+        let transition = TRANSITIONS[usize::from(self.state << 2 | state)];
+        self.state = state;
+
+        let per_step = self.detent as i8;
+        self.remainder += transition;
+        let steps = self.remainder / per_step;
+        self.remainder %= per_step;
+
+        self.position += i32::from(steps);
+        Ok(i32::from(steps))
+    }
+
+    fn decode(data: u16) -> u8 {
+        u8::from(data & (1 << B) != 0) << 1 | u8::from(data & (1 << A) != 0)
+    }
+}
+
+impl<const A: u8, const B: u8, I2C: BusLock> RotaryEncoder<'_, A, B, I2C> {
+    /// Set how many transitions make up a step. Any partial step is dropped.
+    pub fn set_detent(&mut self, detent: Detent) {
+        self.detent = detent;
+        self.remainder = 0;
+    }
+
+    /// The interrupt bits of the encoder's pins, where bit 0 is `a0` and bit
+    /// 15 is `b7`.
+    #[must_use]
+    pub fn mask(&self) -> u16 {
+        1 << A | 1 << B
+    }
+
+    /// The total steps turned since the encoder was created.
+    #[must_use]
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    /// Set the current position, for example to zero it.
+    pub fn set_position(&mut self, position: i32) {
+        self.position = position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::MockBus, reg::Register, Pins, Sx1509};
+
+    type Encoder<'a> = RotaryEncoder<'a, 0, 9, MockBus>;
+
+    /// Set the `BA` state of an encoder on `a0` and `b1`.
+    fn turn_to(bus: &MockBus, state: u8) {
+        bus.set_register(Register::RegDataA as u8, 0xFE | state & 1);
+        bus.set_register(Register::RegDataB as u8, 0xFD | state & 0b10);
+    }
+
+    #[test]
+    fn decode_picks_out_both_pins() {
+        assert_eq!(Encoder::decode(0), 0b00);
+        assert_eq!(Encoder::decode(1 << 0), 0b01);
+        assert_eq!(Encoder::decode(1 << 9), 0b10);
+        assert_eq!(Encoder::decode(!(1 << 0)), 0b10);
+        assert_eq!(Encoder::decode(u16::MAX), 0b11);
+    }
+
+    #[test]
+    fn counts_full_steps_in_both_directions() {
+        let bus = MockBus::new();
+        let mut expander = Sx1509::new(bus.clone(), 0x3E).unwrap();
+        let Pins { a0, b1, .. } = expander.split();
+        let mut knob = RotaryEncoder::new(a0, b1).unwrap();
+
+        // Clockwise from the pulled up rest state, `a` leading `b`.
+        let mut steps = 0;
+        for state in [0b10, 0b00, 0b01, 0b11] {
+            turn_to(&bus, state);
+            steps += knob.update().unwrap();
+        }
+        assert_eq!(steps, 1);
+
+        for state in [0b01, 0b00, 0b10, 0b11] {
+            turn_to(&bus, state);
+            knob.update().unwrap();
+        }
+        assert_eq!(knob.position(), 0);
+    }
+
+    #[test]
+    fn detent_sets_transitions_per_step() {
+        let bus = MockBus::new();
+        let mut expander = Sx1509::new(bus.clone(), 0x3E).unwrap();
+        let Pins { a0, b1, .. } = expander.split();
+        let mut knob = RotaryEncoder::new(a0, b1).unwrap();
+        knob.set_detent(Detent::Half);
+
+        for state in [0b10, 0b00] {
+            turn_to(&bus, state);
+            knob.update().unwrap();
+        }
+        assert_eq!(knob.position(), 1);
+
+        // Both signals changing at once can't be decoded.
+        turn_to(&bus, 0b11);
+        assert_eq!(knob.update().unwrap(), 0);
+    }
+}
@@ -5,6 +5,31 @@ pub enum Error<EI2C> {
     Io(EI2C),
     /// The I2C bus is busy, ie used by another pin at the same time.
     BusBusy,
+    /// The operation needs the oscillator, which is off. Debouncing and the
+    /// keypad engine silently do nothing without it.
+    OscillatorOff,
+    /// The LED driver clock is off, so writing LED intensities or timings
+    /// would have no visible effect. It is started when pins are put into LED
+    /// driver mode, and set by
+    /// [`ClockConfig::led_divider`](crate::clock::ClockConfig::led_divider).
+    LedClockOff,
+    /// Nothing acknowledged the address, so there is no device there. Only
+    /// returned by [`Sx1509::probe`](crate::Sx1509::probe).
+    NotFound,
+    /// A divider was out of range, such as a
+    /// [clock output](crate::Sx1509::set_clock_output) divider not below 14.
+    DividerOutOfRange,
+    /// A register read back differently from what was just written to it,
+    /// with [write verification](crate::Sx1509::set_verify_writes) on. This
+    /// points to bus corruption or another device at the same address.
+    VerifyFailed {
+        /// The address of the register.
+        register: u8,
+        /// The value written.
+        written: u8,
+        /// The value read back.
+        read: u8,
+    },
 }
 
 impl<EI2C> embedded_hal::digital::Error for Error<EI2C>
@@ -16,6 +41,33 @@ where
     }
 }
 
+/// An error from [`Sx1509WithInt`](crate::interrupt::Sx1509WithInt), which
+/// talks to both the expander and the MCU pin wired to NINT.
+#[derive(Debug)]
+pub enum NintError<EI2C, EPIN> {
+    /// An error communicating with the SX1509.
+    Expander(Error<EI2C>),
+    /// An error reading the NINT pin.
+    Pin(EPIN),
+}
+
+impl<EI2C, EPIN> From<Error<EI2C>> for NintError<EI2C, EPIN> {
+    fn from(error: Error<EI2C>) -> Self {
+        Self::Expander(error)
+    }
+}
+
+/// An error from creating the driver with
+/// [`Sx1509::new_with_nreset`](crate::Sx1509::new_with_nreset), which talks to
+/// both the expander and the MCU pin wired to NRESET.
+#[derive(Debug)]
+pub enum NresetError<EI2C, EPIN> {
+    /// An error on the I2C bus.
+    I2c(EI2C),
+    /// An error driving the NRESET pin.
+    Pin(EPIN),
+}
+
 /// An error that occurs when changing the mode of a pin.
 pub struct ModeChange<E, P> {
     /// The inner error that occurred, preventing the mode change.
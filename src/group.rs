@@ -0,0 +1,157 @@
+use crate::{
+    error::{Error, ModeChange},
+    lock::BusLock,
+    Interface, Pin,
+};
+
+mod sealed {
+    pub trait Sealed<'a, I2C: crate::lock::BusLock> {
+        fn interface(&self) -> &'a crate::Interface<I2C>;
+
+        /// Whether every pin belongs to the same expander.
+        fn same_expander(&self) -> bool;
+    }
+}
+
+/// A tuple of 1 to 8 pins, in any order, used as the outputs of a
+/// [`PinGroup`].
+pub trait GroupPins<'a, I2C: BusLock>: sealed::Sealed<'a, I2C> {
+    /// The pin numbers in order, where `0` is `a0` and `15` is `b7`.
+    const PINS: &'static [u8];
+
+    /// A mask of the pins, where bit 0 is `a0` and bit 15 is `b7`.
+    #[must_use]
+    fn mask() -> u16 {
+        Self::PINS.iter().fold(0, |mask, pin| mask | 1 << pin)
+    }
+}
+
+macro_rules! group_pins {
+    ($($pin:ident $field:tt),+) => {
+        impl<'a, I2C: BusLock, $(const $pin: u8),+> sealed::Sealed<'a, I2C> for ($(Pin<'a, $pin, I2C>,)+) {
+            fn interface(&self) -> &'a Interface<I2C> {
+                self.0.interface
+            }
+
+            fn same_expander(&self) -> bool {
+                $(core::ptr::eq(self.0.interface, self.$field.interface))&&+
+            }
+        }
+
+        impl<'a, I2C: BusLock, $(const $pin: u8),+> GroupPins<'a, I2C> for ($(Pin<'a, $pin, I2C>,)+) {
+            const PINS: &'static [u8] = &[$($pin),+];
+        }
+    };
+}
+
+group_pins!(P0 0);
+group_pins!(P0 0, P1 1);
+group_pins!(P0 0, P1 1, P2 2);
+group_pins!(P0 0, P1 1, P2 2, P3 3);
+group_pins!(P0 0, P1 1, P2 2, P3 3, P4 4);
+group_pins!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+group_pins!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6);
+group_pins!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7);
+
+/// Push-pull outputs from one bank that are written together. Every write is
+/// a single write to the bank's data register, so the pins always change at
+/// the same time, such as the 4 bits of a nibble-wide bus.
+///
+/// ```rust
+/// # use sx1509_eh::{group::PinGroup, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let Pins { a0, a1, a2, a3, .. } = expander.split();
+/// let mut nibble = PinGroup::new((a0, a1, a2, a3))?;
+/// nibble.write(0b0101, 0b1010)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct PinGroup<'a, I2C: BusLock, P> {
+    interface: &'a Interface<I2C>,
+    pins: P,
+}
+
+impl<'a, I2C, E, P> PinGroup<'a, I2C, P>
+where
+    I2C: BusLock<Error = E>,
+    P: GroupPins<'a, I2C>,
+{
+    /// Set up `pins` as push-pull outputs driving low. Using pins from both
+    /// banks fails to compile.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the pins can be extracted from the [`ModeChange`],
+    /// although they may be partially configured.
+    ///
+    /// # Panics
+    /// All pins in a group must belong to the same expander. This function
+    /// panics if they don't.
+    pub fn new(pins: P) -> Result<Self, ModeChange<Error<E>, P>> {
+        const {
+            let mut i = 1;
+            while i < P::PINS.len() {
+                assert!(
+                    P::PINS[i] / 8 == P::PINS[0] / 8,
+                    "the pins of a group must be in the same bank"
+                );
+                i += 1;
+            }
+        }
+
+        assert!(
+            pins.same_expander(),
+            "the pins of a group must belong to the same expander"
+        );
+
+        let interface = pins.interface();
+        match interface.enable_group(P::mask()) {
+            Ok(()) => Ok(Self { interface, pins }),
+            Err(error) => Err(ModeChange { error, pin: pins }),
+        }
+    }
+
+    /// Drive the pins in `high` high and the pins in `low` low in one write,
+    /// leaving the rest of the group as is. Both masks have bit 0 as `a0` and
+    /// bit 15 as `b7`, and bits of pins outside the group are ignored. A pin
+    /// in both masks is driven high.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn write(&mut self, high: u16, low: u16) -> Result<(), Error<E>> {
+        let mask = (high | low) & P::mask();
+        self.interface.write_group(mask, high)
+    }
+
+    /// Return the pins, reset to unconfigured inputs.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the group can be extracted from the [`ModeChange`],
+    /// although the pins may be partially reset.
+    pub fn release(self) -> Result<P, ModeChange<Error<E>, Self>> {
+        match self.interface.disable_group(P::mask()) {
+            Ok(()) => Ok(self.pins),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::MockBus, Sx1509};
+
+    #[test]
+    #[should_panic(expected = "same expander")]
+    fn rejects_pins_from_another_expander() {
+        let mut first = Sx1509::new(MockBus::new(), 0x3E).unwrap();
+        let mut second = Sx1509::new(MockBus::new(), 0x3E).unwrap();
+        let _ = PinGroup::new((first.split().a0, second.split().a1));
+    }
+}
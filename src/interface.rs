@@ -1,40 +1,195 @@
-use crate::{error::Error, reg::Register};
+use core::time::Duration;
+
+use embedded_hal::i2c::{I2c, Operation};
+use portable_atomic::{AtomicBool, AtomicU16, AtomicU32, Ordering};
+
+use crate::{
+    clock::{ClockConfig, ClockSource, INTERNAL_OSCILLATOR_HZ},
+    drive::{DriveProfile, DriveStrength, Slew},
+    error::Error,
+    interrupt::Edge,
+    keypad::{ScanTime, SleepTime},
+    led::{FadeMode, LedConfig, NresetFunction},
+    level_shifter::Direction,
+    lock::BusLock,
+    reg::Register,
+    time::IntoDuration,
+};
+
+/// The number of registers in the register map, from `RegInputDisableB` at
+/// `0x00` to `RegHighInputA` at `0x6A`, as read by
+/// [`Sx1509::dump_registers`](crate::Sx1509::dump_registers). The reset and
+/// test registers above them are left out.
+pub const REGISTER_COUNT: usize = 0x6B;
+
+/// The number of registers, from `RegInputDisableB` to `RegKeyConfig2`, kept
+/// in [`Interface`]'s shadow copy.
+const SHADOWED_REGISTERS: usize = 0x27;
+
+/// Set on a shadow entry that holds a known register value.
+const SHADOW_VALID: u16 = 0x100;
+
+/// Set on a shadow entry whose value is waiting for
+/// [`Interface::flush`] to be written to the chip.
+const SHADOW_DIRTY: u16 = 0x200;
+
+/// One of the two 8-pin banks on the SX1509.
+#[derive(Clone, Copy)]
+pub(crate) enum Bank {
+    A,
+    B,
+}
 
 #[derive(Clone, Copy)]
 enum BankAgnosticRegister {
+    InputDisable,
+    LongSlew,
+    LowDrive,
     Dir,
     Data,
     PullUp,
     PullDown,
     OpenDrain,
     DebounceEnable,
+    LedDriverEnable,
+    InterruptMask,
+    InterruptSource,
+    HighInput,
+    Polarity,
 }
 
 impl BankAgnosticRegister {
     pub(crate) const fn into_register<const PIN: u8>(self) -> Register {
         if const { PIN < 8 } {
-            match self {
+            self.into_bank_register(Bank::A)
+        } else {
+            self.into_bank_register(Bank::B)
+        }
+    }
+
+    pub(crate) const fn into_bank_register(self, bank: Bank) -> Register {
+        match bank {
+            Bank::A => match self {
+                BankAgnosticRegister::InputDisable => Register::RegInputDisableA,
+                BankAgnosticRegister::LongSlew => Register::RegLongSlewA,
+                BankAgnosticRegister::LowDrive => Register::RegLowDriveA,
                 BankAgnosticRegister::Dir => Register::RegDirA,
                 BankAgnosticRegister::Data => Register::RegDataA,
                 BankAgnosticRegister::PullUp => Register::RegPullUpA,
                 BankAgnosticRegister::PullDown => Register::RegPullDownA,
                 BankAgnosticRegister::OpenDrain => Register::RegOpenDrainA,
                 BankAgnosticRegister::DebounceEnable => Register::RegDebounceEnableA,
-            }
-        } else {
-            match self {
+                BankAgnosticRegister::LedDriverEnable => Register::RegLEDDriverEnableA,
+                BankAgnosticRegister::InterruptMask => Register::RegInterruptMaskA,
+                BankAgnosticRegister::InterruptSource => Register::RegInterruptSourceA,
+                BankAgnosticRegister::HighInput => Register::RegHighInputA,
+                BankAgnosticRegister::Polarity => Register::RegPolarityA,
+            },
+            Bank::B => match self {
+                BankAgnosticRegister::InputDisable => Register::RegInputDisableB,
+                BankAgnosticRegister::LongSlew => Register::RegLongSlewB,
+                BankAgnosticRegister::LowDrive => Register::RegLowDriveB,
                 BankAgnosticRegister::Dir => Register::RegDirB,
                 BankAgnosticRegister::Data => Register::RegDataB,
                 BankAgnosticRegister::PullUp => Register::RegPullUpB,
                 BankAgnosticRegister::PullDown => Register::RegPullDownB,
                 BankAgnosticRegister::OpenDrain => Register::RegOpenDrainB,
                 BankAgnosticRegister::DebounceEnable => Register::RegDebounceEnableB,
-            }
+                BankAgnosticRegister::LedDriverEnable => Register::RegLEDDriverEnableB,
+                BankAgnosticRegister::InterruptMask => Register::RegInterruptMaskB,
+                BankAgnosticRegister::InterruptSource => Register::RegInterruptSourceB,
+                BankAgnosticRegister::HighInput => Register::RegHighInputB,
+                BankAgnosticRegister::Polarity => Register::RegPolarityB,
+            },
         }
     }
 }
 
-/// Debounce time, if enabled for a certain pin.
+/// The per-pin LED driver registers. Only pins 4-7 and 12-15 have the fade
+/// (`TRise`/`TFall`) registers.
+#[derive(Clone, Copy)]
+pub(crate) enum LedRegister {
+    TOn,
+    IOn,
+    Off,
+    TRise,
+    TFall,
+}
+
+impl LedRegister {
+    pub(crate) const fn into_register(self, pin: u8) -> Option<Register> {
+        Some(match (pin, self) {
+            (0, LedRegister::TOn) => Register::RegTOn0,
+            (0, LedRegister::IOn) => Register::RegIOn0,
+            (0, LedRegister::Off) => Register::RegOff0,
+            (1, LedRegister::TOn) => Register::RegTOn1,
+            (1, LedRegister::IOn) => Register::RegIOn1,
+            (1, LedRegister::Off) => Register::RegOff1,
+            (2, LedRegister::TOn) => Register::RegTOn2,
+            (2, LedRegister::IOn) => Register::RegIOn2,
+            (2, LedRegister::Off) => Register::RegOff2,
+            (3, LedRegister::TOn) => Register::RegTOn3,
+            (3, LedRegister::IOn) => Register::RegIOn3,
+            (3, LedRegister::Off) => Register::RegOff3,
+            (4, LedRegister::TOn) => Register::RegTOn4,
+            (4, LedRegister::IOn) => Register::RegIOn4,
+            (4, LedRegister::Off) => Register::RegOff4,
+            (4, LedRegister::TRise) => Register::RegTRise4,
+            (4, LedRegister::TFall) => Register::RegTFall4,
+            (5, LedRegister::TOn) => Register::RegTOn5,
+            (5, LedRegister::IOn) => Register::RegIOn5,
+            (5, LedRegister::Off) => Register::RegOff5,
+            (5, LedRegister::TRise) => Register::RegTRise5,
+            (5, LedRegister::TFall) => Register::RegTFall5,
+            (6, LedRegister::TOn) => Register::RegTOn6,
+            (6, LedRegister::IOn) => Register::RegIOn6,
+            (6, LedRegister::Off) => Register::RegOff6,
+            (6, LedRegister::TRise) => Register::RegTRise6,
+            (6, LedRegister::TFall) => Register::RegTFall6,
+            (7, LedRegister::TOn) => Register::RegTOn7,
+            (7, LedRegister::IOn) => Register::RegIOn7,
+            (7, LedRegister::Off) => Register::RegOff7,
+            (7, LedRegister::TRise) => Register::RegTRise7,
+            (7, LedRegister::TFall) => Register::RegTFall7,
+            (8, LedRegister::TOn) => Register::RegTOn8,
+            (8, LedRegister::IOn) => Register::RegIOn8,
+            (8, LedRegister::Off) => Register::RegOff8,
+            (9, LedRegister::TOn) => Register::RegTOn9,
+            (9, LedRegister::IOn) => Register::RegIOn9,
+            (9, LedRegister::Off) => Register::RegOff9,
+            (10, LedRegister::TOn) => Register::RegTOn10,
+            (10, LedRegister::IOn) => Register::RegIOn10,
+            (10, LedRegister::Off) => Register::RegOff10,
+            (11, LedRegister::TOn) => Register::RegTOn11,
+            (11, LedRegister::IOn) => Register::RegIOn11,
+            (11, LedRegister::Off) => Register::RegOff11,
+            (12, LedRegister::TOn) => Register::RegTOn12,
+            (12, LedRegister::IOn) => Register::RegIOn12,
+            (12, LedRegister::Off) => Register::RegOff12,
+            (12, LedRegister::TRise) => Register::RegTRise12,
+            (12, LedRegister::TFall) => Register::RegTFall12,
+            (13, LedRegister::TOn) => Register::RegTOn13,
+            (13, LedRegister::IOn) => Register::RegIOn13,
+            (13, LedRegister::Off) => Register::RegOff13,
+            (13, LedRegister::TRise) => Register::RegTRise13,
+            (13, LedRegister::TFall) => Register::RegTFall13,
+            (14, LedRegister::TOn) => Register::RegTOn14,
+            (14, LedRegister::IOn) => Register::RegIOn14,
+            (14, LedRegister::Off) => Register::RegOff14,
+            (14, LedRegister::TRise) => Register::RegTRise14,
+            (14, LedRegister::TFall) => Register::RegTFall14,
+            (15, LedRegister::TOn) => Register::RegTOn15,
+            (15, LedRegister::IOn) => Register::RegIOn15,
+            (15, LedRegister::Off) => Register::RegOff15,
+            (15, LedRegister::TRise) => Register::RegTRise15,
+            (15, LedRegister::TFall) => Register::RegTFall15,
+            _ => return None,
+        })
+    }
+}
+
+/// Debounce time, if enabled for a certain pin. The durations are for the
+/// internal 2MHz oscillator, and scale inversely with other clock frequencies.
 #[derive(Debug, Default, Clone, Copy)]
 pub enum DebounceTime {
     /// 0.5ms
@@ -56,25 +211,312 @@ pub enum DebounceTime {
     Ms64 = 0b111,
 }
 
-pub(crate) struct Interface<I2C> {
-    i2c: spin::Mutex<I2C>,
-    address: u8,
+impl DebounceTime {
+    const ALL: [Self; 8] = [
+        Self::Ms0_5,
+        Self::Ms1,
+        Self::Ms2,
+        Self::Ms4,
+        Self::Ms8,
+        Self::Ms16,
+        Self::Ms32,
+        Self::Ms64,
+    ];
+
+    /// The value in the low bits of `RegDebounceConfig`.
+    pub(crate) const fn from_bits(bits: u8) -> Self {
+        Self::ALL[(bits & 0b111) as usize]
+    }
+
+    /// The debounce time closest to `target` with the internal 2MHz
+    /// oscillator. Use [`duration`](Self::duration) to get the exact result.
+    #[must_use]
+    pub fn from_duration(target: impl IntoDuration) -> Self {
+        Self::from_duration_at(target, INTERNAL_OSCILLATOR_HZ)
+    }
+
+    /// The debounce time closest to `target` with the chip clocked at
+    /// `oscillator_hz`.
+    ///
+    /// # Panics
+    /// This function will panic if `oscillator_hz` is zero.
+    #[must_use]
+    pub fn from_duration_at(target: impl IntoDuration, oscillator_hz: u32) -> Self {
+        let target = target.into_duration();
+        let mut nearest = Self::ALL[0];
+        for time in Self::ALL {
+            if time.duration_at(oscillator_hz).abs_diff(target)
+                < nearest.duration_at(oscillator_hz).abs_diff(target)
+            {
+                nearest = time;
+            }
+        }
+        nearest
+    }
+
+    /// The debounce time with the internal 2MHz oscillator.
+    #[must_use]
+    pub fn duration(self) -> Duration {
+        self.duration_at(INTERNAL_OSCILLATOR_HZ)
+    }
+
+    /// The debounce time with the chip clocked at `oscillator_hz`. The
+    /// shortest time is 1000 clock cycles.
+    ///
+    /// # Panics
+    /// This function will panic if `oscillator_hz` is zero.
+    #[must_use]
+    pub fn duration_at(self, oscillator_hz: u32) -> Duration {
+        let cycles = 1000_u64 << self as u8;
+        Duration::from_nanos(cycles * 1_000_000_000 / u64::from(oscillator_hz))
+    }
 }
 
-impl<I2C, E> Interface<I2C>
-where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
-{
-    pub(crate) fn new(i2c: spin::Mutex<I2C>, address: u8) -> Self {
-        Self { i2c, address }
+/// The settings changed by
+/// [`Sx1509::enter_low_power`](crate::Sx1509::enter_low_power), as raw
+/// register values.
+#[derive(Debug, Default, Clone)]
+pub struct LowPowerState {
+    /// `RegInterruptMaskB` to `RegSenseLowA`.
+    interrupts: [u8; 6],
+    /// `RegDebounceEnableB` to `RegKeyConfig2`.
+    debounce_keypad: [u8; 4],
+    /// `RegClock` and `RegMisc`.
+    clock_misc: [u8; 2],
+    /// The frequency of `fOSC`, which can't be read back for external clocks.
+    oscillator_hz: u32,
+}
+
+/// The settings in `RegMisc`, decoded. Read them with
+/// [`Sx1509::misc_config`](crate::Sx1509::misc_config), change the fields and
+/// write them back with
+/// [`Sx1509::set_misc_config`](crate::Sx1509::set_misc_config). Register
+/// address auto-increment isn't included, as the driver's burst transfers
+/// need it on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MiscConfig {
+    /// The fade mode of the bank A LEDs.
+    pub fade_mode_a: FadeMode,
+    /// The fade mode of the bank B LEDs.
+    pub fade_mode_b: FadeMode,
+    /// The LED driver clock `ClkX` runs at `fOSC` divided by
+    /// `2^(led_clock_divider - 1)`, or is off if this is `0`. Only the low 3
+    /// bits are used.
+    pub led_clock_divider: u8,
+    /// What a low pulse on NRESET does.
+    pub nreset_function: NresetFunction,
+    /// Whether reading the data registers clears NINT.
+    pub nint_autoclear: bool,
+}
+
+impl MiscConfig {
+    /// Decode the value of `RegMisc`.
+    const fn from_bits(bits: u8) -> Self {
+        const fn fade_mode(bit: u8) -> FadeMode {
+            if bit == 0 {
+                FadeMode::Linear
+            } else {
+                FadeMode::Logarithmic
+            }
+        }
+
+        Self {
+            fade_mode_a: fade_mode(bits >> 3 & 1),
+            fade_mode_b: fade_mode(bits >> 7 & 1),
+            led_clock_divider: bits >> 4 & 0b111,
+            nreset_function: if bits >> 2 & 1 == 0 {
+                NresetFunction::Reset
+            } else {
+                NresetFunction::LedSync
+            },
+            nint_autoclear: bits & 1 == 0,
+        }
     }
 
-    pub(crate) fn set_output<const PIN: u8>(&self) -> Result<(), Error<E>> {
-        self.unset_bit::<PIN>(BankAgnosticRegister::Dir)
+    /// Encode the value of `RegMisc`, with auto-increment on.
+    const fn bits(self) -> u8 {
+        (matches!(self.fade_mode_b, FadeMode::Logarithmic) as u8) << 7
+            | (self.led_clock_divider & 0b111) << 4
+            | (matches!(self.fade_mode_a, FadeMode::Logarithmic) as u8) << 3
+            | (matches!(self.nreset_function, NresetFunction::LedSync) as u8) << 2
+            | !self.nint_autoclear as u8
     }
+}
 
-    pub(crate) fn set_input<const PIN: u8>(&self) -> Result<(), Error<E>> {
-        self.set_bit::<PIN>(BankAgnosticRegister::Dir)
+/// Counts of the I2C transfers made by the driver, read with
+/// [`Sx1509::stats`](crate::Sx1509::stats). They are only kept with the
+/// `stats` feature, and are otherwise always `0`. Transfers made through
+/// [`Sx1509::with_bus`](crate::Sx1509::with_bus) aren't counted, and the
+/// counts wrap around on overflow.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Register reads, whether of one register or a burst.
+    pub reads: u32,
+    /// Register writes, whether of one register or a burst.
+    pub writes: u32,
+    /// Extra tries made by the [`RetryPolicy`] after a failed transfer.
+    pub retries: u32,
+    /// Times the bus lock was already held, failing with
+    /// [`Error::BusBusy`].
+    pub bus_busy: u32,
+}
+
+/// The counters behind [`Stats`], indexing `Interface::stats`.
+#[derive(Clone, Copy)]
+enum Counter {
+    Reads,
+    Writes,
+    Retries,
+    BusBusy,
+}
+
+/// The configuration of a single pin as the chip holds it, decoded. Read it
+/// with [`Sx1509::pin_config`](crate::Sx1509::pin_config) to check what the
+/// typestates claim against the hardware.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinConfig {
+    /// Whether the pin is an output, rather than an input.
+    pub output: bool,
+    /// Whether the input buffer is off.
+    pub input_disabled: bool,
+    /// Whether the output has slow edges.
+    pub long_slew: bool,
+    /// Whether the output has reduced drive strength.
+    pub low_drive: bool,
+    /// Whether the pull-up is on.
+    pub pull_up: bool,
+    /// Whether the pull-down is on.
+    pub pull_down: bool,
+    /// Whether the output is open-drain, rather than push-pull.
+    pub open_drain: bool,
+    /// Whether the pin is inverted in hardware.
+    pub inverted: bool,
+    /// Whether the input is debounced.
+    pub debounce: bool,
+    /// Whether the input accepts logic levels above its bank's supply.
+    pub high_input: bool,
+}
+
+/// How many times a failed I2C transfer is tried before its error is
+/// returned, for long or noisy buses where the odd NACK is expected. Set it
+/// with [`Sx1509::set_retry_policy`](crate::Sx1509::set_retry_policy).
+///
+/// ```rust
+/// # use sx1509_eh::{RetryPolicy, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # mod cortex_m {
+/// #     pub mod asm {
+/// #         pub fn delay(_: u32) {}
+/// #     }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// // Up to three tries, 100us apart on a 64MHz core.
+/// let policy = RetryPolicy::new(3).delay(100, |us| cortex_m::asm::delay(us * 64));
+/// expander.set_retry_policy(policy);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    attempts: u8,
+    delay_us: u32,
+    delay: Option<fn(u32)>,
+}
+
+impl RetryPolicy {
+    /// Try every transfer up to `attempts` times, straight after each other.
+    ///
+    /// # Panics
+    /// This function will panic if `attempts` is `0`.
+    #[must_use]
+    pub const fn new(attempts: u8) -> Self {
+        assert!(attempts > 0, "a transfer needs at least one attempt");
+        Self {
+            attempts,
+            delay_us: 0,
+            delay: None,
+        }
+    }
+
+    /// Wait `us` microseconds between tries, by calling `delay` with it. The
+    /// bus is unlocked while waiting.
+    #[must_use]
+    pub const fn delay(mut self, us: u32, delay: fn(u32)) -> Self {
+        self.delay_us = us;
+        self.delay = Some(delay);
+        self
+    }
+
+    /// How many times a transfer is tried.
+    #[must_use]
+    pub const fn attempts(&self) -> u8 {
+        self.attempts
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Every transfer is tried once.
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// Public so that it can appear in sealed traits, but not reachable from
+/// outside the crate.
+pub struct Interface<I2C: BusLock> {
+    i2c: I2C::Mutex,
+    address: u8,
+    /// The frequency of `fOSC` in Hz, or `0` if it is off. Debouncing needs
+    /// it running, and timings scale with it.
+    oscillator_hz: AtomicU32,
+    /// Whether the LED driver clock `ClkX` is running, without which the LED
+    /// registers have no visible effect.
+    led_clock_on: AtomicBool,
+    /// Whether every write is read back and compared.
+    verify_writes: AtomicBool,
+    /// The last known value of each register up to `RegKeyConfig2`, with
+    /// [`SHADOW_VALID`] set once it is known, so that changing a few bits
+    /// doesn't have to read the register first. For the data registers this
+    /// is the output latch, which can't be read back.
+    shadow: [AtomicU16; SHADOWED_REGISTERS],
+    /// How failed transfers are retried.
+    retry: RetryPolicy,
+    /// Whether to wait for the bus lock rather than fail with
+    /// [`Error::BusBusy`].
+    blocking_lock: AtomicBool,
+    /// Whether writes to shadowed registers are held back until
+    /// [`flush`](Self::flush).
+    batching: AtomicBool,
+    /// The transfer counts, indexed by [`Counter`].
+    #[cfg(feature = "stats")]
+    stats: [AtomicU32; 4],
+}
+
+impl<I2C, E> Interface<I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// The driver sets up the clock according to `clock` before creating the
+    /// interface.
+    pub(crate) fn new(i2c: I2C::Mutex, address: u8, clock: &ClockConfig) -> Self {
+        Self {
+            i2c,
+            address,
+            oscillator_hz: AtomicU32::new(clock.hz()),
+            led_clock_on: AtomicBool::new(clock.led_clock_divider() != 0),
+            verify_writes: AtomicBool::new(false),
+            shadow: [const { AtomicU16::new(0) }; SHADOWED_REGISTERS],
+            retry: RetryPolicy::new(1),
+            blocking_lock: AtomicBool::new(false),
+            batching: AtomicBool::new(false),
+            #[cfg(feature = "stats")]
+            stats: [const { AtomicU32::new(0) }; 4],
+        }
     }
 
     pub(crate) fn set_data<const PIN: u8>(&self, value: bool) -> Result<(), Error<E>> {
@@ -85,6 +527,12 @@ where
         }
     }
 
+    pub(crate) fn toggle_data<const PIN: u8>(&self) -> Result<(), Error<E>> {
+        let register = BankAgnosticRegister::Data.into_register::<PIN>();
+        let existing_data = self.read_shadow(register)?;
+        self.write(register, existing_data ^ (1 << (PIN % 8)))
+    }
+
     pub(crate) fn get_data<const PIN: u8>(&self) -> Result<bool, Error<E>> {
         self.get_bit::<PIN>(BankAgnosticRegister::Data)
     }
@@ -115,30 +563,915 @@ where
 
     pub(crate) fn set_debounce_enable<const PIN: u8>(&self, value: bool) -> Result<(), Error<E>> {
         if value {
+            self.require_oscillator()?;
             self.set_bit::<PIN>(BankAgnosticRegister::DebounceEnable)
         } else {
             self.unset_bit::<PIN>(BankAgnosticRegister::DebounceEnable)
         }
     }
 
+    /// Make the pin assert NINT on `edge`, by setting its sense bits and
+    /// unmasking it.
+    pub(crate) fn enable_interrupt<const PIN: u8>(&self, edge: Edge) -> Result<(), Error<E>> {
+        let bank = if PIN < 8 { Bank::A } else { Bank::B };
+        self.set_bank_sense(bank, 1 << (PIN % 8), edge as u8)?;
+        self.unset_bit::<PIN>(BankAgnosticRegister::InterruptMask)
+    }
+
+    pub(crate) fn set_input_disable<const PIN: u8>(&self, value: bool) -> Result<(), Error<E>> {
+        if value {
+            self.set_bit::<PIN>(BankAgnosticRegister::InputDisable)
+        } else {
+            self.unset_bit::<PIN>(BankAgnosticRegister::InputDisable)
+        }
+    }
+
+    pub(crate) fn set_long_slew<const PIN: u8>(&self, value: bool) -> Result<(), Error<E>> {
+        if value {
+            self.set_bit::<PIN>(BankAgnosticRegister::LongSlew)
+        } else {
+            self.unset_bit::<PIN>(BankAgnosticRegister::LongSlew)
+        }
+    }
+
+    /// Write both long slew registers in one burst, where bit 0 is `a0` and
+    /// bit 15 is `b7`. A set bit slows the pin's edges.
+    pub(crate) fn set_long_slew_mask(&self, mask: u16) -> Result<(), Error<E>> {
+        self.write_pair(Register::RegLongSlewB, mask)
+    }
+
+    pub(crate) fn set_low_drive<const PIN: u8>(&self, value: bool) -> Result<(), Error<E>> {
+        if value {
+            self.set_bit::<PIN>(BankAgnosticRegister::LowDrive)
+        } else {
+            self.unset_bit::<PIN>(BankAgnosticRegister::LowDrive)
+        }
+    }
+
+    /// Write both low drive registers in one burst, where bit 0 is `a0` and
+    /// bit 15 is `b7`. A set bit reduces the pin's drive strength.
+    pub(crate) fn set_low_drive_mask(&self, mask: u16) -> Result<(), Error<E>> {
+        self.write_pair(Register::RegLowDriveB, mask)
+    }
+
+    pub(crate) fn set_high_input<const PIN: u8>(&self, value: bool) -> Result<(), Error<E>> {
+        if value {
+            self.set_bit::<PIN>(BankAgnosticRegister::HighInput)
+        } else {
+            self.unset_bit::<PIN>(BankAgnosticRegister::HighInput)
+        }
+    }
+
+    /// Write both high input registers in one burst, where bit 0 is `a0` and
+    /// bit 15 is `b7`. A set bit lets the pin accept levels above VCC.
+    pub(crate) fn set_high_input_mask(&self, mask: u16) -> Result<(), Error<E>> {
+        self.write_pair(Register::RegHighInputB, mask)
+    }
+
+    /// Apply `profile` to every pin in `mask`, where bit 0 is `a0` and bit 15
+    /// is `b7`, leaving the other pins as they are. The long slew and low
+    /// drive registers are adjacent, so they are updated in one burst.
+    pub(crate) fn set_drive_profile(
+        &self,
+        mask: u16,
+        profile: DriveProfile,
+    ) -> Result<(), Error<E>> {
+        let update = |bytes: [u8; 2], value: bool| -> [u8; 2] {
+            let bits = u16::from_be_bytes(bytes);
+            if value { bits | mask } else { bits & !mask }.to_be_bytes()
+        };
+
+        let mut drive = [0; 4];
+        self.read_burst(Register::RegLongSlewB as u8, &mut drive)?;
+        let [slew_b, slew_a] = update([drive[0], drive[1]], profile.slew == Slew::Long);
+        let [low_b, low_a] = update(
+            [drive[2], drive[3]],
+            profile.drive_strength == DriveStrength::Low,
+        );
+        self.write_burst(
+            Register::RegLongSlewB as u8,
+            &[slew_b, slew_a, low_b, low_a],
+        )?;
+
+        let mut high_input = [0; 2];
+        self.read_burst(Register::RegHighInputB as u8, &mut high_input)?;
+        self.write_burst(
+            Register::RegHighInputB as u8,
+            &update(high_input, profile.high_input),
+        )
+    }
+
+    pub(crate) fn set_polarity<const PIN: u8>(&self, inverted: bool) -> Result<(), Error<E>> {
+        if inverted {
+            self.set_bit::<PIN>(BankAgnosticRegister::Polarity)
+        } else {
+            self.unset_bit::<PIN>(BankAgnosticRegister::Polarity)
+        }
+    }
+
+    pub(crate) fn get_polarity<const PIN: u8>(&self) -> Result<bool, Error<E>> {
+        self.get_bit::<PIN>(BankAgnosticRegister::Polarity)
+    }
+
+    /// Write both polarity registers in one burst, where bit 0 is `a0` and
+    /// bit 15 is `b7`. A set bit inverts the pin.
+    pub(crate) fn set_polarity_mask(&self, mask: u16) -> Result<(), Error<E>> {
+        self.write_pair(Register::RegPolarityB, mask)
+    }
+
+    /// Set the level shifter mode of the pair `a{pair}` and `b{pair}`, or turn
+    /// it off with `None`. Like the bank registers, the higher pairs come
+    /// first: pairs 4-7 are in `RegLevelShifter1` and pairs 0-3 in
+    /// `RegLevelShifter2`, two bits each.
+    pub(crate) fn set_level_shifter(
+        &self,
+        pair: u8,
+        direction: Option<Direction>,
+    ) -> Result<(), Error<E>> {
+        let register = if pair < 4 {
+            Register::RegLevelShifter2
+        } else {
+            Register::RegLevelShifter1
+        };
+        let shift = 2 * (pair % 4);
+        let mode = direction.map_or(0b00, |direction| direction as u8);
+        self.update_bits(register, 0b11 << shift, mode << shift)
+    }
+
+    /// Write both input buffer disable registers in one burst, where bit 0 is
+    /// `a0` and bit 15 is `b7`. A set bit turns the buffer off.
+    pub(crate) fn set_input_disable_mask(&self, mask: u16) -> Result<(), Error<E>> {
+        self.write_pair(Register::RegInputDisableB, mask)
+    }
+
+    /// Write both interrupt mask registers in one burst, where bit 0 is `a0`
+    /// and bit 15 is `b7`. A set bit masks the pin.
+    pub(crate) fn set_interrupt_mask(&self, mask: u16) -> Result<(), Error<E>> {
+        self.write_pair(Register::RegInterruptMaskB, mask)
+    }
+
+    /// Write all four sense registers in one burst. Pins in both masks sense
+    /// both edges, and pins in neither sense nothing.
+    pub(crate) fn set_sense(&self, rising: u16, falling: u16) -> Result<(), Error<E>> {
+        self.write_burst(
+            Register::RegSenseHighB as u8,
+            &sense_registers(rising, falling),
+        )
+    }
+
+    /// Restore every pin configuration register, from `RegInputDisableB` to
+    /// `RegDirA`, to its power-on value, making every pin an input with no
+    /// pulls, then disable the LED driver and debouncing on every pin.
+    pub(crate) fn set_safe_defaults(&self) -> Result<(), Error<E>> {
+        let mut config = [0; 16];
+        config[14..].copy_from_slice(&u16::MAX.to_be_bytes());
+        self.write_burst(Register::RegInputDisableB as u8, &config)?;
+        self.write_burst(Register::RegLEDDriverEnableB as u8, &[0, 0])?;
+        self.write_burst(Register::RegDebounceEnableB as u8, &[0, 0])
+    }
+
+    #[cfg(feature = "raw")]
+    pub(crate) fn read_register(&self, register: Register) -> Result<u8, Error<E>> {
+        self.read(register)
+    }
+
+    #[cfg(feature = "raw")]
+    pub(crate) fn write_register(&self, register: Register, data: u8) -> Result<(), Error<E>> {
+        self.write(register, data)
+    }
+
+    /// Read every register from `0x00` to `0x6A` in one burst.
+    pub(crate) fn dump_registers(&self) -> Result<[u8; REGISTER_COUNT], Error<E>> {
+        let mut registers = [0; REGISTER_COUNT];
+        self.read_burst(Register::RegInputDisableB as u8, &mut registers)?;
+        Ok(registers)
+    }
+
+    /// Read the configuration of `PIN` back from the chip, using one burst
+    /// for the configuration registers from `RegInputDisableB` to `RegDirA`
+    /// and one each for the debounce and high input registers.
+    pub(crate) fn pin_config<const PIN: u8>(&self) -> Result<PinConfig, Error<E>> {
+        let mut config = [0; 16];
+        self.read_burst(Register::RegInputDisableB as u8, &mut config)?;
+        let mut debounce = [0; 2];
+        self.read_burst(Register::RegDebounceEnableB as u8, &mut debounce)?;
+        let mut high_input = [0; 2];
+        self.read_burst(Register::RegHighInputB as u8, &mut high_input)?;
+
+        let bit = |[b, a]: [u8; 2]| u16::from_be_bytes([b, a]) >> PIN & 1 != 0;
+        let pair = |index: usize| bit([config[2 * index], config[2 * index + 1]]);
+        Ok(PinConfig {
+            output: !pair(7),
+            input_disabled: pair(0),
+            long_slew: pair(1),
+            low_drive: pair(2),
+            pull_up: pair(3),
+            pull_down: pair(4),
+            open_drain: pair(5),
+            inverted: pair(6),
+            debounce: bit(debounce),
+            high_input: bit(high_input),
+        })
+    }
+
+    /// Save the settings that [`exit_low_power`](Self::exit_low_power)
+    /// restores, then make only the pins in `rising` and `falling` able to
+    /// assert NINT, and stop the keypad engine, LED clock and oscillator.
+    /// Debouncing needs the oscillator, so it is turned off on the wake pins.
+    pub(crate) fn enter_low_power(
+        &self,
+        rising: u16,
+        falling: u16,
+    ) -> Result<LowPowerState, Error<E>> {
+        let mut state = LowPowerState::default();
+        self.read_burst(Register::RegInterruptMaskB as u8, &mut state.interrupts)?;
+        self.read_burst(
+            Register::RegDebounceEnableB as u8,
+            &mut state.debounce_keypad,
+        )?;
+        self.read_burst(Register::RegClock as u8, &mut state.clock_misc)?;
+        state.oscillator_hz = self.oscillator_hz();
+
+        let wake = rising | falling;
+        self.clear_interrupts(u16::MAX)?;
+
+        let mut interrupts = [0; 6];
+        interrupts[..2].copy_from_slice(&(!wake).to_be_bytes());
+        interrupts[2..].copy_from_slice(&sense_registers(rising, falling));
+        self.write_burst(Register::RegInterruptMaskB as u8, &interrupts)?;
+
+        let debounce = u16::from_be_bytes([state.debounce_keypad[0], state.debounce_keypad[1]]);
+        let [debounce_b, debounce_a] = (debounce & !wake).to_be_bytes();
+        self.write_burst(
+            Register::RegDebounceEnableB as u8,
+            &[debounce_b, debounce_a, 0, 0],
+        )?;
+
+        let [_, misc] = state.clock_misc;
+        self.write_burst(Register::RegClock as u8, &[0, misc & !0b0111_0000])?;
+        self.oscillator_hz.store(0, Ordering::Relaxed);
+        self.led_clock_on.store(false, Ordering::Relaxed);
+
+        Ok(state)
+    }
+
+    /// Restore the settings saved by [`enter_low_power`](Self::enter_low_power).
+    /// The oscillator is started first, so the rest comes back in a working
+    /// order. Interrupts are left pending, so the wake source can be read.
+    pub(crate) fn exit_low_power(&self, state: &LowPowerState) -> Result<(), Error<E>> {
+        self.write_burst(Register::RegClock as u8, &state.clock_misc)?;
+        self.oscillator_hz
+            .store(state.oscillator_hz, Ordering::Relaxed);
+        self.led_clock_on
+            .store(state.clock_misc[1] & 0b0111_0000 != 0, Ordering::Relaxed);
+        self.write_burst(Register::RegDebounceEnableB as u8, &state.debounce_keypad)?;
+        self.write_burst(Register::RegInterruptMaskB as u8, &state.interrupts)
+    }
+
+    /// Read back the edge the pin is sensitive to, or `None` if it isn't.
+    pub(crate) fn interrupt_edge<const PIN: u8>(&self) -> Result<Option<Edge>, Error<E>> {
+        let start = if PIN < 8 {
+            Register::RegSenseHighA
+        } else {
+            Register::RegSenseHighB
+        };
+
+        let mut data = [0; 2];
+        self.read_burst(start as u8, &mut data)?;
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(Edge::from_bits(
+            (u16::from_be_bytes(data) >> (2 * (PIN % 8))) as u8,
+        ))
+    }
+
+    pub(crate) fn disable_interrupt<const PIN: u8>(&self) -> Result<(), Error<E>> {
+        self.set_bit::<PIN>(BankAgnosticRegister::InterruptMask)
+    }
+
     pub(crate) fn set_debounce_time(&self, debounce_time: DebounceTime) -> Result<(), Error<E>> {
+        self.require_oscillator()?;
         self.write(Register::RegDebounceConfig, debounce_time as u8)
     }
+
+    /// Set the debounce time closest to `target` at the current `fOSC`,
+    /// returning the actual time.
+    pub(crate) fn set_debounce_duration(&self, target: Duration) -> Result<Duration, Error<E>> {
+        self.require_oscillator()?;
+        let debounce_time = DebounceTime::from_duration_at(target, self.oscillator_hz());
+        self.write(Register::RegDebounceConfig, debounce_time as u8)?;
+        Ok(debounce_time.duration_at(self.oscillator_hz()))
+    }
+
+    /// The configured debounce time at the current `fOSC`, or `None` if the
+    /// oscillator is off.
+    pub(crate) fn debounce_duration(&self) -> Result<Option<Duration>, Error<E>> {
+        let debounce_time = DebounceTime::from_bits(self.read(Register::RegDebounceConfig)?);
+        let oscillator_hz = self.oscillator_hz();
+        Ok((oscillator_hz != 0).then(|| debounce_time.duration_at(oscillator_hz)))
+    }
+
+    /// Put every pin in `mask` into LED driver mode, following the sequence
+    /// from the datasheet: input buffer off, pull-up off, open-drain output,
+    /// LED driver on and the data bit cleared so the driver is active.
+    pub(crate) fn enable_led_driver_bank(&self, bank: Bank, mask: u8) -> Result<(), Error<E>> {
+        self.set_bank_bits(bank, BankAgnosticRegister::InputDisable, mask)?;
+        self.unset_bank_bits(bank, BankAgnosticRegister::PullUp, mask)?;
+        self.set_bank_bits(bank, BankAgnosticRegister::OpenDrain, mask)?;
+        self.unset_bank_bits(bank, BankAgnosticRegister::Dir, mask)?;
+        self.set_bank_bits(bank, BankAgnosticRegister::LedDriverEnable, mask)?;
+        self.unset_bank_bits(bank, BankAgnosticRegister::Data, mask)?;
+        self.enable_led_clock()
+    }
+
+    /// Put `PIN` into LED driver mode with a single coalesced write, see
+    /// [`enable_led_driver_bank`](Self::enable_led_driver_bank).
+    pub(crate) fn enable_led_driver<const PIN: u8>(&self) -> Result<(), Error<E>> {
+        self.coalesce(Stage::enable_led_driver::<PIN>)
+    }
+
+    pub(crate) fn set_led_intensity<const PIN: u8>(&self, intensity: u8) -> Result<(), Error<E>> {
+        self.require_led_clock()?;
+        self.write_led::<PIN>(LedRegister::IOn, intensity)
+    }
+
+    pub(crate) fn set_led_blink<const PIN: u8>(
+        &self,
+        on_time: u8,
+        off_time: u8,
+        off_intensity: u8,
+    ) -> Result<(), Error<E>> {
+        self.require_led_clock()?;
+        self.write_led::<PIN>(LedRegister::TOn, on_time & 0b1_1111)?;
+        self.write_led::<PIN>(
+            LedRegister::Off,
+            (off_time & 0b1_1111) << 3 | (off_intensity & 0b111),
+        )
+    }
+
+    pub(crate) fn set_led_fade<const PIN: u8>(&self, rise: u8, fall: u8) -> Result<(), Error<E>> {
+        const {
+            assert!(
+                LedRegister::TRise.into_register(PIN).is_some(),
+                "only pins 4-7 and 12-15 support fading"
+            );
+        }
+
+        self.require_led_clock()?;
+        self.write_led::<PIN>(LedRegister::TRise, rise & 0b1_1111)?;
+        self.write_led::<PIN>(LedRegister::TFall, fall & 0b1_1111)
+    }
+
+    /// Write all the LED driver registers of a pin in one burst. The fade
+    /// registers are only written on pins that have them.
+    pub(crate) fn configure_led<const PIN: u8>(&self, config: LedConfig) -> Result<(), Error<E>> {
+        self.require_led_clock()?;
+        let Some(start) = LedRegister::TOn.into_register(PIN) else {
+            unreachable!("every pin has an on time register");
+        };
+        let data = [
+            config.on_time & 0b1_1111,
+            config.on_intensity,
+            (config.off_time & 0b1_1111) << 3 | (config.off_intensity & 0b111),
+            config.rise_time & 0b1_1111,
+            config.fall_time & 0b1_1111,
+        ];
+
+        if LedRegister::TRise.into_register(PIN).is_some() {
+            self.write_burst(start as u8, &data)
+        } else {
+            self.write_burst(start as u8, &data[..3])
+        }
+    }
+
+    /// Start the keypad engine with `rows` rows on bank A and `columns`
+    /// columns on bank B. The rows are driven open-drain and the columns are
+    /// debounced inputs with pull-ups, as the engine requires.
+    pub(crate) fn enable_keypad(&self, rows: u8, columns: u8) -> Result<(), Error<E>> {
+        self.require_oscillator()?;
+        let row_mask = u8::MAX >> (8 - rows);
+        let column_mask = u8::MAX >> (8 - columns);
+
+        self.unset_bank_bits(Bank::A, BankAgnosticRegister::Dir, row_mask)?;
+        self.set_bank_bits(Bank::A, BankAgnosticRegister::OpenDrain, row_mask)?;
+        self.set_bank_bits(Bank::B, BankAgnosticRegister::Dir, column_mask)?;
+        self.set_bank_bits(Bank::B, BankAgnosticRegister::PullUp, column_mask)?;
+        self.unset_bank_bits(Bank::B, BankAgnosticRegister::PullDown, column_mask)?;
+        self.set_bank_bits(Bank::B, BankAgnosticRegister::DebounceEnable, column_mask)?;
+
+        // Auto sleep off, 1ms (at 2MHz) scan time per row.
+        self.write(Register::RegKeyConfig1, 0)?;
+        self.write(Register::RegKeyConfig2, (rows - 1) << 3 | (columns - 1))
+    }
+
+    /// Turn off the keypad engine and put the pins it used back into their
+    /// power-on state: inputs without pulls, debouncing or interrupts.
+    pub(crate) fn disable_keypad(&self, rows: u8, columns: u8) -> Result<(), Error<E>> {
+        let row_mask = u8::MAX >> (8 - rows);
+        let column_mask = u8::MAX >> (8 - columns);
+
+        // Zero rows turns the engine off.
+        self.write(Register::RegKeyConfig2, 0)?;
+        self.write(Register::RegKeyConfig1, 0)?;
+
+        self.set_bank_bits(Bank::A, BankAgnosticRegister::Dir, row_mask)?;
+        self.unset_bank_bits(Bank::A, BankAgnosticRegister::OpenDrain, row_mask)?;
+        self.set_bank_bits(Bank::B, BankAgnosticRegister::InterruptMask, column_mask)?;
+        self.set_bank_sense(Bank::B, column_mask, 0)?;
+        self.unset_bank_bits(Bank::B, BankAgnosticRegister::PullUp, column_mask)?;
+        self.unset_bank_bits(Bank::B, BankAgnosticRegister::DebounceEnable, column_mask)
+    }
+
+    pub(crate) fn set_keypad_scan_time(&self, scan_time: ScanTime) -> Result<(), Error<E>> {
+        self.update_bits(Register::RegKeyConfig1, 0b0000_0111, scan_time as u8)
+    }
+
+    pub(crate) fn set_keypad_sleep_time(&self, sleep_time: SleepTime) -> Result<(), Error<E>> {
+        self.update_bits(
+            Register::RegKeyConfig1,
+            0b0111_0000,
+            (sleep_time as u8) << 4,
+        )
+    }
+
+    /// Make the keypad columns in `column_mask` raise NINT on a key press, or
+    /// stop them from doing so.
+    pub(crate) fn set_keypad_interrupt(
+        &self,
+        column_mask: u8,
+        enabled: bool,
+    ) -> Result<(), Error<E>> {
+        if enabled {
+            self.set_bank_sense(Bank::B, column_mask, Edge::Falling as u8)?;
+            self.unset_bank_bits(Bank::B, BankAgnosticRegister::InterruptMask, column_mask)
+        } else {
+            self.set_bank_bits(Bank::B, BankAgnosticRegister::InterruptMask, column_mask)
+        }
+    }
+
+    /// Read both interrupt source registers in one burst, where bit 0 is
+    /// `a0` and bit 15 is `b7`.
+    pub(crate) fn interrupt_source(&self) -> Result<u16, Error<E>> {
+        self.read_pair(Register::RegInterruptSourceB)
+    }
+
+    /// Read the event status registers of both banks, where bit 0 is `a0` and
+    /// bit 15 is `b7`. Unlike the interrupt source, these also latch edges on
+    /// masked pins.
+    pub(crate) fn event_status(&self) -> Result<u16, Error<E>> {
+        self.read_pair(Register::RegEventStatusB)
+    }
+
+    /// Read the event status registers and clear the bits that were set, so
+    /// events latched after the read are kept for next time.
+    pub(crate) fn take_events(&self) -> Result<u16, Error<E>> {
+        let mut data = [0; 2];
+        self.read_burst(Register::RegEventStatusB as u8, &mut data)?;
+        if data != [0; 2] {
+            self.write_burst(Register::RegEventStatusB as u8, &data)?;
+        }
+        Ok(u16::from_be_bytes(data))
+    }
+
+    /// Clear the interrupt source and event status bits of the pins in
+    /// `mask`, where bit 0 is `a0` and bit 15 is `b7`. The registers are
+    /// consecutive, so this is a single burst write.
+    pub(crate) fn clear_interrupts(&self, mask: u16) -> Result<(), Error<E>> {
+        let [b, a] = mask.to_be_bytes();
+        self.write_burst(Register::RegInterruptSourceB as u8, &[b, a, b, a])
+    }
+
+    /// Clear the interrupt source bits of the pins in `mask`. The bits are
+    /// cleared by writing a one to them.
+    pub(crate) fn clear_bank_interrupts(&self, bank: Bank, mask: u8) -> Result<(), Error<E>> {
+        self.write(
+            BankAgnosticRegister::InterruptSource.into_bank_register(bank),
+            mask,
+        )
+    }
+
+    /// Read the keypad data registers, returning the pressed column and row
+    /// bits. The registers are active low, so this inverts them. Reading
+    /// them also clears the keypad interrupt.
+    pub(crate) fn key_data(&self) -> Result<(u8, u8), Error<E>> {
+        let mut data = [0; 2];
+        self.read_burst(Register::RegKeyData1 as u8, &mut data)?;
+        Ok((!data[0], !data[1]))
+    }
+
+    /// Set or clear the data bits of every pin in `mask`, where bit 0 is `a0`
+    /// and bit 15 is `b7`.
+    pub(crate) fn set_data_mask(&self, mask: u16, value: bool) -> Result<(), Error<E>> {
+        self.update_mask(BankAgnosticRegister::Data, mask, value)
+    }
+
+    /// Write the data bits of every pin in one burst, where bit 0 is `a0` and
+    /// bit 15 is `b7`.
+    pub(crate) fn write_data(&self, data: u16) -> Result<(), Error<E>> {
+        let [b, a] = data.to_be_bytes();
+        match (
+            self.cached(Register::RegDataB) != Some(b),
+            self.cached(Register::RegDataA) != Some(a),
+        ) {
+            (true, true) => self.write_pair(Register::RegDataB, data),
+            (true, false) => self.write(Register::RegDataB, b),
+            (false, true) => self.write(Register::RegDataA, a),
+            (false, false) => Ok(()),
+        }
+    }
+
+    /// Write all eight data bits of `bank` at once.
+    pub(crate) fn write_bank_data(&self, bank: Bank, data: u8) -> Result<(), Error<E>> {
+        self.write(BankAgnosticRegister::Data.into_bank_register(bank), data)
+    }
+
+    /// Read the data bits of every pin in one burst, where bit 0 is `a0` and
+    /// bit 15 is `b7`.
+    pub(crate) fn get_data_mask(&self) -> Result<u16, Error<E>> {
+        self.read_pair(Register::RegDataB)
+    }
+
+    /// Set up the pins of a software scanned key matrix. The rows become
+    /// released open-drain outputs and the columns become inputs with
+    /// pull-ups.
+    pub(crate) fn enable_matrix(&self, rows: u16, columns: u16) -> Result<(), Error<E>> {
+        self.update_mask(BankAgnosticRegister::Data, rows, true)?;
+        self.update_mask(BankAgnosticRegister::OpenDrain, rows, true)?;
+        self.update_mask(BankAgnosticRegister::Dir, rows, false)?;
+        self.update_mask(BankAgnosticRegister::Dir, columns, true)?;
+        self.update_mask(BankAgnosticRegister::PullDown, columns, false)?;
+        self.update_mask(BankAgnosticRegister::PullUp, columns, true)
+    }
+
+    /// Put the pins of a software scanned key matrix back into their
+    /// power-on state.
+    pub(crate) fn disable_matrix(&self, rows: u16, columns: u16) -> Result<(), Error<E>> {
+        self.update_mask(BankAgnosticRegister::Dir, rows, true)?;
+        self.update_mask(BankAgnosticRegister::OpenDrain, rows, false)?;
+        self.update_mask(BankAgnosticRegister::PullUp, columns, false)
+    }
+
+    /// Set up the pins of a group as push-pull outputs driving low.
+    pub(crate) fn enable_group(&self, mask: u16) -> Result<(), Error<E>> {
+        self.update_mask(BankAgnosticRegister::Data, mask, false)?;
+        self.update_mask(BankAgnosticRegister::OpenDrain, mask, false)?;
+        self.update_mask(BankAgnosticRegister::Dir, mask, false)
+    }
+
+    /// Write the data bits of the pins in `mask` from `bits`, with a single
+    /// write as all the pins are in one bank.
+    pub(crate) fn write_group(&self, mask: u16, bits: u16) -> Result<(), Error<E>> {
+        for ((bank, mask), (_, bits)) in split_mask(mask).into_iter().zip(split_mask(bits)) {
+            if mask == 0 {
+                continue;
+            }
+
+            self.update_bits(
+                BankAgnosticRegister::Data.into_bank_register(bank),
+                mask,
+                bits,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Put the pins of a group back into their power-on state.
+    pub(crate) fn disable_group(&self, mask: u16) -> Result<(), Error<E>> {
+        self.update_mask(BankAgnosticRegister::Dir, mask, true)?;
+        self.update_mask(BankAgnosticRegister::Data, mask, true)
+    }
+
+    /// Read all the LED driver registers of a pin in one burst. The fade times
+    /// are reported as `0` on pins that don't have fade registers.
+    pub(crate) fn led_config<const PIN: u8>(&self) -> Result<LedConfig, Error<E>> {
+        let Some(start) = LedRegister::TOn.into_register(PIN) else {
+            unreachable!("every pin has an on time register");
+        };
+        let mut data = [0; 5];
+        if LedRegister::TRise.into_register(PIN).is_some() {
+            self.read_burst(start as u8, &mut data)?;
+        } else {
+            self.read_burst(start as u8, &mut data[..3])?;
+        }
+
+        Ok(LedConfig {
+            on_intensity: data[1],
+            on_time: data[0] & 0b1_1111,
+            off_time: data[2] >> 3,
+            off_intensity: data[2] & 0b111,
+            rise_time: data[3] & 0b1_1111,
+            fall_time: data[4] & 0b1_1111,
+        })
+    }
+
+    /// The frequency of the LED driver clock `ClkX`, or `None` if it is off.
+    pub(crate) fn led_clock_hz(&self) -> Result<Option<u32>, Error<E>> {
+        let divider = (self.read(Register::RegMisc)? >> 4) & 0b111;
+        let oscillator_hz = self.oscillator_hz();
+        Ok((divider != 0 && oscillator_hz != 0).then(|| oscillator_hz >> (divider - 1)))
+    }
+
+    /// Select where `fOSC` comes from, keeping the rest of `RegClock`.
+    pub(crate) fn set_clock_source(&self, source: ClockSource) -> Result<(), Error<E>> {
+        self.update_bits(Register::RegClock, 0b0110_0000, source.bits() << 5)?;
+        self.oscillator_hz.store(source.hz(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Rewrite `RegClock` and the `ClkX` bits of `RegMisc` in one burst,
+    /// keeping the rest of `RegMisc`.
+    pub(crate) fn set_clock_config(&self, config: &ClockConfig) -> Result<(), Error<E>> {
+        let misc = self.read_shadow(Register::RegMisc)? & !0b0111_0000 | config.misc_bits();
+        self.write_burst(Register::RegClock as u8, &[config.clock_bits(), misc])?;
+        self.oscillator_hz.store(config.hz(), Ordering::Relaxed);
+        self.led_clock_on
+            .store(config.led_clock_divider() != 0, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Write the reset sequence to `RegReset`, and forget the clock state,
+    /// which is off after a reset.
+    pub(crate) fn reset(&self) -> Result<(), Error<E>> {
+        self.write(Register::RegReset, 0x12)?;
+        self.write(Register::RegReset, 0x34)?;
+        self.invalidate_shadow();
+        self.batching.store(false, Ordering::Relaxed);
+        self.oscillator_hz.store(0, Ordering::Relaxed);
+        self.led_clock_on.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub(crate) fn start_batch(&self) {
+        self.batching.store(true, Ordering::Relaxed);
+    }
+
+    /// Run `f` to stage the register changes of a mode change, then write
+    /// the changed registers together, so that mode changes touching several
+    /// registers cost as few transactions as possible. The changes are staged
+    /// in `f`'s own [`Stage`] and written under a single lock of the bus, so
+    /// other contexts see either none of them or all of them. The registers
+    /// are written in address order rather than the order `f` changed them
+    /// in. If `f` fails, nothing is written. Inside a batch started with
+    /// [`start_batch`](Self::start_batch), the writes join it.
+    pub(crate) fn coalesce<'a>(
+        &'a self,
+        f: impl FnOnce(&mut Stage<'a, I2C>) -> Result<(), Error<E>>,
+    ) -> Result<(), Error<E>> {
+        let mut stage = Stage {
+            interface: self,
+            registers: [0; SHADOWED_REGISTERS],
+            led_clock: false,
+        };
+        f(&mut stage)?;
+        self.commit(&stage.registers)?;
+        if stage.led_clock {
+            self.led_clock_on.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    /// Write every register changed since [`start_batch`](Self::start_batch)
+    /// and stop batching. Changed registers are grouped into bursts, which
+    /// also cover any unchanged registers between them whose value is known,
+    /// so most batches take only a few transactions. If a burst fails, the
+    /// rest stay pending and batching stays on, so the flush can be retried.
+    pub(crate) fn flush(&self) -> Result<(), Error<E>> {
+        let (runs, count) = self.runs(|register| {
+            self.shadow[usize::from(register)].load(Ordering::Relaxed) & SHADOW_DIRTY != 0
+        });
+        for &(start, end) in &runs[..count] {
+            self.flush_run(start, end)?;
+        }
+        self.batching.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Group the shadowed registers for which `changed` is true into runs
+    /// that can each be written in one burst, bridging gaps of registers that
+    /// [`can_fill`](Self::can_fill). Returns the runs, from their first to
+    /// their last register, and how many there are.
+    fn runs(&self, changed: impl Fn(u8) -> bool) -> ([(u8, u8); SHADOWED_REGISTERS], usize) {
+        let mut runs = [(0, 0); SHADOWED_REGISTERS];
+        let mut count = 0;
+        let mut run: Option<(u8, u8)> = None;
+        for register in (0u8..).take(SHADOWED_REGISTERS) {
+            if !changed(register) {
+                continue;
+            }
+
+            run = match run {
+                Some((start, end)) if (end + 1..register).all(|gap| self.can_fill(gap)) => {
+                    Some((start, register))
+                }
+                Some(full) => {
+                    runs[count] = full;
+                    count += 1;
+                    Some((register, register))
+                }
+                None => Some((register, register)),
+            };
+        }
+
+        if let Some(last) = run {
+            runs[count] = last;
+            count += 1;
+        }
+        (runs, count)
+    }
+
+    /// Write the registers staged by [`coalesce`](Self::coalesce) whose value
+    /// differs from the known one, grouped into bursts that are all sent
+    /// under one lock of the bus.
+    fn commit(&self, staged: &[u16; SHADOWED_REGISTERS]) -> Result<(), Error<E>> {
+        let mut data = [0; SHADOWED_REGISTERS];
+        let mut changed = [false; SHADOWED_REGISTERS];
+        for ((register, &entry), (data, changed)) in
+            (0u8..).zip(staged).zip(data.iter_mut().zip(&mut changed))
+        {
+            let known = self.shadow[usize::from(register)].load(Ordering::Relaxed);
+            if entry & SHADOW_DIRTY != 0 {
+                [_, *data] = entry.to_be_bytes();
+                *changed = known & SHADOW_VALID == 0 || known & 0xFF != entry & 0xFF;
+            } else {
+                [_, *data] = known.to_be_bytes();
+            }
+        }
+
+        let (runs, count) = self.runs(|register| changed[usize::from(register)]);
+        let runs = &runs[..count];
+        let burst = |&(start, end): &(u8, u8)| &data[usize::from(start)..=usize::from(end)];
+        if self.batching.load(Ordering::Relaxed) {
+            for run in runs {
+                self.write_burst(run.0, burst(run))?;
+            }
+            return Ok(());
+        }
+
+        for _ in runs {
+            self.count(Counter::Writes);
+        }
+        let result = self.transfer(|i2c| {
+            for run in runs {
+                i2c.transaction(
+                    self.address,
+                    &mut [Operation::Write(&[run.0]), Operation::Write(burst(run))],
+                )?;
+            }
+            Ok(())
+        });
+        if let Err(error) = result {
+            // Some of the bursts may have been written before the failure.
+            for &(start, end) in runs {
+                for entry in &self.shadow[usize::from(start)..=usize::from(end)] {
+                    entry.store(0, Ordering::Relaxed);
+                }
+            }
+            return Err(error);
+        }
+
+        for run in runs {
+            self.store_shadow(run.0, burst(run));
+        }
+        for run in runs {
+            self.verify(run.0, burst(run))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn release(self) -> I2C {
+        I2C::from_mutex(self.i2c)
+    }
+
+    pub(crate) fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry = policy;
+    }
+
+    pub(crate) fn set_blocking_lock(&self, enabled: bool) {
+        self.blocking_lock.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set_verify_writes(&self, enabled: bool) {
+        self.verify_writes.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Read back `RegClock` and `RegMisc` in one burst.
+    pub(crate) fn clock_config(&self) -> Result<ClockConfig, Error<E>> {
+        let mut data = [0; 2];
+        self.read_burst(Register::RegClock as u8, &mut data)?;
+        Ok(ClockConfig::from_bits(
+            data[0],
+            data[1],
+            self.oscillator_hz(),
+        ))
+    }
+
+    /// Drive `fOSC >> divider` out of OSCIO, returning its frequency.
+    pub(crate) fn set_clock_output(&self, divider: u8) -> Result<u32, Error<E>> {
+        if divider >= 14 {
+            return Err(Error::DividerOutOfRange);
+        }
+        self.update_bits(Register::RegClock, 0b1_1111, 0b1_0000 | (divider + 1))?;
+        Ok(self.oscillator_hz() >> divider)
+    }
+
+    /// Drive OSCIO statically high or low, as a plain output.
+    pub(crate) fn set_oscio(&self, high: bool) -> Result<(), Error<E>> {
+        let frequency = if high { 0b1111 } else { 0b0000 };
+        self.update_bits(Register::RegClock, 0b1_1111, 0b1_0000 | frequency)
+    }
+
+    /// Whether OSCIO is driven statically high.
+    pub(crate) fn get_oscio(&self) -> Result<bool, Error<E>> {
+        Ok(self.read(Register::RegClock)? & 0b1_1111 == 0b1_1111)
+    }
+
+    /// The frequency of `fOSC` in Hz, or `0` if it is off.
+    pub(crate) fn oscillator_hz(&self) -> u32 {
+        self.oscillator_hz.load(Ordering::Relaxed)
+    }
+
+    /// Write the intensity of every LED that has `Some` value. The LED
+    /// registers of a bank are contiguous, so each bank costs one burst read
+    /// and one burst write covering the span of staged LEDs, rather than one
+    /// write per LED.
+    pub(crate) fn set_led_intensities(
+        &self,
+        intensities: &[Option<u8>; 16],
+    ) -> Result<(), Error<E>> {
+        self.require_led_clock()?;
+        for bank in [0..8, 8..16] {
+            let staged = || {
+                bank.clone().filter_map(|pin: u8| {
+                    intensities[usize::from(pin)].map(|value| (intensity_register(pin), value))
+                })
+            };
+            let (Some((first, _)), Some((last, _))) = (staged().next(), staged().next_back())
+            else {
+                continue;
+            };
+
+            let mut block = [0; 32];
+            let block = &mut block[..=usize::from(last - first)];
+            self.read_burst(first, block)?;
+            for (register, value) in staged() {
+                block[usize::from(register - first)] = value;
+            }
+            self.write_burst(first, block)?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn set_nreset_function(&self, function: NresetFunction) -> Result<(), Error<E>> {
+        let misc = self.read_shadow(Register::RegMisc)?;
+        let new_misc = match function {
+            NresetFunction::Reset => misc & !0b0000_0100,
+            NresetFunction::LedSync => misc | 0b0000_0100,
+        };
+        self.write(Register::RegMisc, new_misc)
+    }
+
+    pub(crate) fn misc_config(&self) -> Result<MiscConfig, Error<E>> {
+        Ok(MiscConfig::from_bits(self.read(Register::RegMisc)?))
+    }
+
+    pub(crate) fn set_misc_config(&self, config: MiscConfig) -> Result<(), Error<E>> {
+        self.write(Register::RegMisc, config.bits())?;
+        self.led_clock_on
+            .store(config.led_clock_divider & 0b111 != 0, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// The auto-clear bit in `RegMisc` is active low.
+    pub(crate) fn set_nint_autoclear(&self, enabled: bool) -> Result<(), Error<E>> {
+        self.update_bits(Register::RegMisc, 0b0000_0001, u8::from(!enabled))
+    }
+
+    /// The LED drivers are clocked from `ClkX` in `RegMisc`, which is off after
+    /// reset. If it is off, run it directly from the oscillator.
+    fn enable_led_clock(&self) -> Result<(), Error<E>> {
+        let misc = self.read_shadow(Register::RegMisc)?;
+        if misc & 0b0111_0000 == 0 {
+            self.write(Register::RegMisc, misc | 0b0001_0000)?;
+        }
+        self.led_clock_on.store(true, Ordering::Relaxed);
+        Ok(())
+    }
 }
 
 impl<I2C, E> Interface<I2C>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     fn set_bit<const PIN: u8>(&self, bar: BankAgnosticRegister) -> Result<(), Error<E>> {
         let register = bar.into_register::<PIN>();
 
         if const { PIN < 8 } {
-            let existing_data = self.read(register)?;
+            let existing_data = self.read_shadow(register)?;
             let new_data = existing_data | (1 << PIN);
             self.write(register, new_data)
         } else {
-            let existing_data = self.read(register)?;
+            let existing_data = self.read_shadow(register)?;
             let new_data = existing_data | (1 << (PIN - 8));
             self.write(register, new_data)
         }
@@ -148,11 +1481,11 @@ where
         let register = bar.into_register::<PIN>();
 
         if const { PIN < 8 } {
-            let existing_data = self.read(register)?;
+            let existing_data = self.read_shadow(register)?;
             let new_data = existing_data & !(1 << PIN);
             self.write(register, new_data)
         } else {
-            let existing_data = self.read(register)?;
+            let existing_data = self.read_shadow(register)?;
             let new_data = existing_data & !(1 << (PIN - 8));
             self.write(register, new_data)
         }
@@ -170,22 +1503,665 @@ where
         }
     }
 
-    fn write(&self, register: Register, data: u8) -> Result<(), Error<E>> {
-        self.i2c
-            .try_lock()
-            .ok_or(Error::BusBusy)?
-            .write(self.address, &[register as u8, data])
-            .map_err(Error::Io)?;
+    fn write_led<const PIN: u8>(&self, led: LedRegister, data: u8) -> Result<(), Error<E>> {
+        match led.into_register(PIN) {
+            Some(register) => self.write(register, data),
+            None => unreachable!("pin {PIN} has no such LED register"),
+        }
+    }
+
+    /// Set the 2 bit edge sensitivity field of every pin in `mask`. Each bank
+    /// has a high (pins 4-7) and low (pins 0-3) sense register, next to each
+    /// other, so both are updated in one burst.
+    fn set_bank_sense(&self, bank: Bank, mask: u8, sense: u8) -> Result<(), Error<E>> {
+        let start = match bank {
+            Bank::A => Register::RegSenseHighA,
+            Bank::B => Register::RegSenseHighB,
+        };
+
+        let mut data = [0; 2];
+        self.read_burst(start as u8, &mut data)?;
+        let mut value = u16::from_be_bytes(data);
+        for pin in (0..8).filter(|pin| mask & (1 << pin) != 0) {
+            value = value & !(0b11 << (2 * pin)) | u16::from(sense & 0b11) << (2 * pin);
+        }
+        self.write_burst(start as u8, &value.to_be_bytes())
+    }
+
+    /// Set or clear the bits of every pin in `mask` in both banks of `bar`,
+    /// skipping banks with no pins in the mask.
+    fn update_mask(
+        &self,
+        bar: BankAgnosticRegister,
+        mask: u16,
+        value: bool,
+    ) -> Result<(), Error<E>> {
+        for (bank, mask) in split_mask(mask) {
+            if mask == 0 {
+                continue;
+            }
+
+            if value {
+                self.set_bank_bits(bank, bar, mask)?;
+            } else {
+                self.unset_bank_bits(bank, bar, mask)?;
+            }
+        }
+
         Ok(())
     }
 
+    /// Fail with [`Error::OscillatorOff`] if the oscillator isn't running.
+    fn require_oscillator(&self) -> Result<(), Error<E>> {
+        if self.oscillator_hz() != 0 {
+            Ok(())
+        } else {
+            Err(Error::OscillatorOff)
+        }
+    }
+
+    /// Fail if the LED registers would have no visible effect, because the
+    /// oscillator or the LED driver clock is off.
+    fn require_led_clock(&self) -> Result<(), Error<E>> {
+        self.require_oscillator()?;
+        if self.led_clock_on.load(Ordering::Relaxed) {
+            Ok(())
+        } else {
+            Err(Error::LedClockOff)
+        }
+    }
+
+    /// Replace the bits of `register` selected by `mask` with `bits`.
+    fn update_bits(&self, register: Register, mask: u8, bits: u8) -> Result<(), Error<E>> {
+        let existing_data = self.read_shadow(register)?;
+        self.write(register, existing_data & !mask | bits & mask)
+    }
+
+    fn set_bank_bits(
+        &self,
+        bank: Bank,
+        bar: BankAgnosticRegister,
+        mask: u8,
+    ) -> Result<(), Error<E>> {
+        let register = bar.into_bank_register(bank);
+        let existing_data = self.read_shadow(register)?;
+        self.write(register, existing_data | mask)
+    }
+
+    fn unset_bank_bits(
+        &self,
+        bank: Bank,
+        bar: BankAgnosticRegister,
+        mask: u8,
+    ) -> Result<(), Error<E>> {
+        let register = bar.into_bank_register(bank);
+        let existing_data = self.read_shadow(register)?;
+        self.write(register, existing_data & !mask)
+    }
+
+    /// Lock the bus and hand it to `f`, failing with [`Error::BusBusy`] if a
+    /// pin is using it, unless the blocking lock is on.
+    pub(crate) fn with_bus<R>(&self, f: impl FnOnce(&mut I2C::Bus) -> R) -> Result<R, Error<E>> {
+        if self.blocking_lock.load(Ordering::Relaxed) {
+            I2C::lock(&self.i2c, f)
+        } else {
+            I2C::try_lock(&self.i2c, f)
+        }
+        .ok_or_else(|| {
+            self.count(Counter::BusBusy);
+            Error::BusBusy
+        })
+    }
+
+    #[cfg_attr(not(feature = "stats"), allow(clippy::unused_self))]
+    pub(crate) fn stats(&self) -> Stats {
+        #[cfg(feature = "stats")]
+        {
+            let [reads, writes, retries, bus_busy] = self
+                .stats
+                .each_ref()
+                .map(|count| count.load(Ordering::Relaxed));
+            Stats {
+                reads,
+                writes,
+                retries,
+                bus_busy,
+            }
+        }
+        #[cfg(not(feature = "stats"))]
+        Stats::default()
+    }
+
+    #[cfg_attr(not(feature = "stats"), allow(clippy::unused_self))]
+    pub(crate) fn reset_stats(&self) {
+        #[cfg(feature = "stats")]
+        for count in &self.stats {
+            count.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Add one to `counter`, if counts are kept.
+    #[cfg_attr(not(feature = "stats"), allow(clippy::unused_self))]
+    fn count(&self, counter: Counter) {
+        #[cfg(feature = "stats")]
+        self.stats[counter as usize].fetch_add(1, Ordering::Relaxed);
+        #[cfg(not(feature = "stats"))]
+        let _ = counter;
+    }
+
+    /// Run the bus transfer `f`, trying it again according to the retry
+    /// policy if it fails. The bus is unlocked between tries.
+    fn transfer(&self, mut f: impl FnMut(&mut I2C::Bus) -> Result<(), E>) -> Result<(), Error<E>> {
+        let mut attempts = self.retry.attempts;
+        loop {
+            attempts -= 1;
+            match self.with_bus(&mut f)? {
+                Ok(()) => return Ok(()),
+                Err(_) if attempts > 0 => {
+                    self.count(Counter::Retries);
+                    if let Some(delay) = self.retry.delay {
+                        delay(self.retry.delay_us);
+                    }
+                }
+                Err(error) => return Err(Error::Io(error)),
+            }
+        }
+    }
+
+    fn write(&self, register: Register, data: u8) -> Result<(), Error<E>> {
+        if self.defer(register as u8, &[data]) {
+            return Ok(());
+        }
+
+        self.count(Counter::Writes);
+        self.transfer(|i2c| i2c.write(self.address, &[register as u8, data]))?;
+        self.store_shadow(register as u8, &[data]);
+        self.verify(register as u8, &[data])
+    }
+
     fn read(&self, register: Register) -> Result<u8, Error<E>> {
         let mut data = [0];
-        self.i2c
-            .try_lock()
-            .ok_or(Error::BusBusy)?
-            .write_read(self.address, &[register as u8], &mut data)
-            .map_err(Error::Io)?;
+        self.count(Counter::Reads);
+        self.transfer(|i2c| i2c.write_read(self.address, &[register as u8], &mut data))?;
+        self.overlay_pending(register as u8, &mut data);
         Ok(data[0])
     }
+
+    /// Write `value` to a pair of bank registers in one burst, where `start`
+    /// is the bank B register and bit 0 of `value` is `a0`, relying on the
+    /// bank A register following it.
+    fn write_pair(&self, start: Register, value: u16) -> Result<(), Error<E>> {
+        self.write_burst(start as u8, &value.to_be_bytes())
+    }
+
+    /// Read a pair of bank registers in one burst, where `start` is the bank
+    /// B register and bit 0 of the result is `a0`.
+    fn read_pair(&self, start: Register) -> Result<u16, Error<E>> {
+        let mut data = [0; 2];
+        self.read_burst(start as u8, &mut data)?;
+        Ok(u16::from_be_bytes(data))
+    }
+
+    /// Read `register` from the shadow copy if its value is known, or from
+    /// the chip otherwise, for changing some of its bits. For the data
+    /// registers this is the output latch once it has been written.
+    fn read_shadow(&self, register: Register) -> Result<u8, Error<E>> {
+        if let Some(data) = self.cached(register) {
+            return Ok(data);
+        }
+
+        if (register as usize) < SHADOWED_REGISTERS && reads_back(register as u8) {
+            self.fill_shadow()?;
+            if let Some(data) = self.cached(register) {
+                return Ok(data);
+            }
+        }
+        self.read(register)
+    }
+
+    /// Read every shadowed register in one burst and record the ones whose
+    /// value isn't known yet, so that the next changes to them, and the
+    /// bursts that [`flush`](Self::flush) can make, don't need another read.
+    fn fill_shadow(&self) -> Result<(), Error<E>> {
+        let mut data = [0; SHADOWED_REGISTERS];
+        self.read_burst(0, &mut data)?;
+        for ((register, data), entry) in (0u8..).zip(data).zip(&self.shadow) {
+            if reads_back(register) {
+                // Leave entries that became known in the meantime.
+                let _ = entry.compare_exchange(
+                    0,
+                    SHADOW_VALID | u16::from(data),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The shadow copy of `register`, if its value is known.
+    fn cached(&self, register: Register) -> Option<u8> {
+        let entry = self.shadow.get(register as usize)?.load(Ordering::Relaxed);
+        if entry & SHADOW_VALID == 0 {
+            return None;
+        }
+
+        let [_, data] = entry.to_be_bytes();
+        Some(data)
+    }
+
+    /// Record `data` as written to the registers from `start`. Writes to the
+    /// interrupt source and event status registers clear bits rather than
+    /// set them, so they aren't recorded.
+    fn store_shadow(&self, start: u8, data: &[u8]) {
+        for (register, &data) in (start..).zip(data) {
+            if matches!(register, 0x18..=0x1B) {
+                continue;
+            }
+            if let Some(entry) = self.shadow.get(usize::from(register)) {
+                entry.store(SHADOW_VALID | u16::from(data), Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// While batching, record `data` as pending for the registers from
+    /// `start` instead of writing it, if they are all shadowed.
+    fn defer(&self, start: u8, data: &[u8]) -> bool {
+        let deferrable = |register: u8| {
+            usize::from(register) < SHADOWED_REGISTERS && !matches!(register, 0x18..=0x1B)
+        };
+        if !self.batching.load(Ordering::Relaxed)
+            || !(start..)
+                .zip(data)
+                .all(|(register, _)| deferrable(register))
+        {
+            return false;
+        }
+
+        for (register, &data) in (start..).zip(data) {
+            self.shadow[usize::from(register)].store(
+                SHADOW_VALID | SHADOW_DIRTY | u16::from(data),
+                Ordering::Relaxed,
+            );
+        }
+        true
+    }
+
+    /// Replace the registers from `start` just read from the chip with their
+    /// pending values, so reads see the batched configuration. The data
+    /// registers read the pins rather than the latch, so they are left.
+    fn overlay_pending(&self, start: u8, data: &mut [u8]) {
+        for (register, data) in (start..).zip(data) {
+            if let Some(entry) = self.shadow.get(usize::from(register)) {
+                let entry = entry.load(Ordering::Relaxed);
+                if entry & SHADOW_DIRTY != 0 && reads_back(register) {
+                    [_, *data] = entry.to_be_bytes();
+                }
+            }
+        }
+    }
+
+    /// Whether the shadowed `register` can be rewritten with its known value
+    /// to join two bursts.
+    fn can_fill(&self, register: u8) -> bool {
+        self.shadow[usize::from(register)].load(Ordering::Relaxed) & SHADOW_VALID != 0
+            && !matches!(register, 0x18..=0x1B)
+    }
+
+    /// Write the shadowed registers from `start` to `end` in one burst.
+    fn flush_run(&self, start: u8, end: u8) -> Result<(), Error<E>> {
+        let mut data = [0; SHADOWED_REGISTERS];
+        let data = &mut data[..=usize::from(end - start)];
+        for (data, entry) in data.iter_mut().zip(&self.shadow[usize::from(start)..]) {
+            [_, *data] = entry.load(Ordering::Relaxed).to_be_bytes();
+        }
+        self.transmit(start, data)
+    }
+
+    /// Forget every shadowed register value, after a reset or when the chip
+    /// may no longer hold what was written.
+    fn invalidate_shadow(&self) {
+        for entry in &self.shadow {
+            entry.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Write consecutive registers starting at `start`, relying on the
+    /// chip's register address auto-increment.
+    fn write_burst(&self, start: u8, data: &[u8]) -> Result<(), Error<E>> {
+        if self.defer(start, data) {
+            return Ok(());
+        }
+
+        self.transmit(start, data)
+    }
+
+    /// Write consecutive registers starting at `start` straight to the chip.
+    fn transmit(&self, start: u8, data: &[u8]) -> Result<(), Error<E>> {
+        self.count(Counter::Writes);
+        self.transfer(|i2c| {
+            i2c.transaction(
+                self.address,
+                &mut [Operation::Write(&[start]), Operation::Write(data)],
+            )
+        })?;
+        self.store_shadow(start, data);
+        self.verify(start, data)
+    }
+
+    /// With write verification on, read back the registers from `start` that
+    /// were just written with `data`, and fail on the first mismatch.
+    /// Registers that don't read back what was written are skipped.
+    fn verify(&self, start: u8, data: &[u8]) -> Result<(), Error<E>> {
+        if !self.verify_writes.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+
+        for (chunk, data) in (start..).step_by(16).zip(data.chunks(16)) {
+            let mut read = [0; 16];
+            let read = &mut read[..data.len()];
+            self.read_burst(chunk, read)?;
+            for ((register, &written), &read) in (chunk..).zip(data).zip(read.iter()) {
+                if written != read && reads_back(register) {
+                    self.invalidate_shadow();
+                    return Err(Error::VerifyFailed {
+                        register,
+                        written,
+                        read,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Read consecutive registers starting at `start`, relying on the chip's
+    /// register address auto-increment.
+    fn read_burst(&self, start: u8, data: &mut [u8]) -> Result<(), Error<E>> {
+        self.count(Counter::Reads);
+        self.transfer(|i2c| i2c.write_read(self.address, &[start], data))?;
+        self.overlay_pending(start, data);
+        Ok(())
+    }
+}
+
+/// The register changes of a mode change, staged by [`Interface::coalesce`]
+/// and only written once they are all known.
+pub(crate) struct Stage<'i, I2C: BusLock> {
+    interface: &'i Interface<I2C>,
+    /// The staged value of each shadowed register, with [`SHADOW_DIRTY`] set
+    /// on the ones that were changed.
+    registers: [u16; SHADOWED_REGISTERS],
+    /// Whether the LED driver clock is started by the staged changes.
+    led_clock: bool,
+}
+
+impl<I2C, E> Stage<'_, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    pub(crate) fn set_output<const PIN: u8>(&mut self) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::Dir, false)
+    }
+
+    pub(crate) fn set_input<const PIN: u8>(&mut self) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::Dir, true)
+    }
+
+    pub(crate) fn set_pull_up<const PIN: u8>(&mut self, value: bool) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::PullUp, value)
+    }
+
+    pub(crate) fn set_pull_down<const PIN: u8>(&mut self, value: bool) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::PullDown, value)
+    }
+
+    pub(crate) fn set_open_drain<const PIN: u8>(&mut self, value: bool) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::OpenDrain, value)
+    }
+
+    pub(crate) fn set_input_disable<const PIN: u8>(&mut self, value: bool) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::InputDisable, value)
+    }
+
+    pub(crate) fn set_debounce_enable<const PIN: u8>(
+        &mut self,
+        value: bool,
+    ) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::DebounceEnable, value)
+    }
+
+    pub(crate) fn enable_interrupt<const PIN: u8>(&mut self, edge: Edge) -> Result<(), Error<E>> {
+        self.set_sense::<PIN>(Some(edge))?;
+        self.update_bit::<PIN>(BankAgnosticRegister::InterruptMask, false)
+    }
+
+    /// Set the edge the pin is sensitive to, or turn its sensing off.
+    pub(crate) fn set_sense<const PIN: u8>(&mut self, edge: Option<Edge>) -> Result<(), Error<E>> {
+        let (high, low) = if PIN < 8 {
+            (Register::RegSenseHighA, Register::RegSenseLowA)
+        } else {
+            (Register::RegSenseHighB, Register::RegSenseLowB)
+        };
+        let shift = 2 * (PIN % 8);
+        let sense = u16::from_be_bytes([self.read(high)?, self.read(low)?]);
+        let [high_data, low_data] = (sense & !(0b11 << shift)
+            | u16::from(edge.map_or(0, |edge| edge as u8)) << shift)
+            .to_be_bytes();
+        self.write(high, high_data);
+        self.write(low, low_data);
+        Ok(())
+    }
+
+    pub(crate) fn disable_interrupt<const PIN: u8>(&mut self) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::InterruptMask, true)
+    }
+
+    /// See [`Interface::enable_led_driver_bank`].
+    pub(crate) fn enable_led_driver<const PIN: u8>(&mut self) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::InputDisable, true)?;
+        self.update_bit::<PIN>(BankAgnosticRegister::PullUp, false)?;
+        self.update_bit::<PIN>(BankAgnosticRegister::OpenDrain, true)?;
+        self.update_bit::<PIN>(BankAgnosticRegister::Dir, false)?;
+        self.update_bit::<PIN>(BankAgnosticRegister::LedDriverEnable, true)?;
+        self.update_bit::<PIN>(BankAgnosticRegister::Data, false)?;
+
+        let misc = self.read(Register::RegMisc)?;
+        if misc & 0b0111_0000 == 0 {
+            self.write(Register::RegMisc, misc | 0b0001_0000);
+        }
+        self.led_clock = true;
+        Ok(())
+    }
+
+    pub(crate) fn disable_led_driver<const PIN: u8>(&mut self) -> Result<(), Error<E>> {
+        self.update_bit::<PIN>(BankAgnosticRegister::LedDriverEnable, false)?;
+        self.update_bit::<PIN>(BankAgnosticRegister::InputDisable, false)
+    }
+
+    fn update_bit<const PIN: u8>(
+        &mut self,
+        bar: BankAgnosticRegister,
+        value: bool,
+    ) -> Result<(), Error<E>> {
+        let register = bar.into_register::<PIN>();
+        let bit = 1 << (PIN % 8);
+        let data = self.read(register)?;
+        self.write(register, if value { data | bit } else { data & !bit });
+        Ok(())
+    }
+
+    /// The staged value of `register`, or its current value if it hasn't
+    /// been changed.
+    fn read(&self, register: Register) -> Result<u8, Error<E>> {
+        let entry = self.registers[register as usize];
+        if entry & SHADOW_DIRTY == 0 {
+            return self.interface.read_shadow(register);
+        }
+
+        let [_, data] = entry.to_be_bytes();
+        Ok(data)
+    }
+
+    fn write(&mut self, register: Register, data: u8) {
+        self.registers[register as usize] = SHADOW_DIRTY | u16::from(data);
+    }
+}
+
+/// The values of `RegSenseHighB`, `RegSenseLowB`, `RegSenseHighA` and
+/// `RegSenseLowA` for the given edge masks.
+fn sense_registers(rising: u16, falling: u16) -> [u8; 4] {
+    let bank = |shift: u16| -> [u8; 2] {
+        let [rising, falling] = [rising, falling].map(|mask| (mask >> shift) & 0xFF);
+        let value = (0..8).fold(0u16, |value, pin| {
+            let edge = (rising >> pin & 1) | (falling >> pin & 1) << 1;
+            value | edge << (2 * pin)
+        });
+        value.to_be_bytes()
+    };
+
+    let [high_b, low_b] = bank(8);
+    let [high_a, low_a] = bank(0);
+    [high_b, low_b, high_a, low_a]
+}
+
+/// Whether reading `register` returns what was last written to it. The data
+/// registers read the pins, the interrupt and event registers are cleared by
+/// writing ones, the key data registers are read-only and the reset register
+/// takes a sequence.
+fn reads_back(register: u8) -> bool {
+    !matches!(register, 0x10 | 0x11 | 0x18..=0x1B | 0x27 | 0x28 | 0x7D)
+}
+
+/// Split a 16 bit pin mask into its bank A and bank B halves.
+fn split_mask(mask: u16) -> [(Bank, u8); 2] {
+    let [a, b] = mask.to_le_bytes();
+    [(Bank::A, a), (Bank::B, b)]
+}
+
+fn intensity_register(pin: u8) -> u8 {
+    match LedRegister::IOn.into_register(pin) {
+        Some(register) => register as u8,
+        None => unreachable!("every pin has an intensity register"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec;
+
+    use super::*;
+    use crate::{
+        led::{FadeMode, NresetFunction},
+        mock::MockBus,
+    };
+
+    #[test]
+    fn sense_registers_pack_two_bits_per_pin() {
+        // a0 rising, a5 falling and b7 on both edges.
+        let rising = 1 << 0 | 1 << 15;
+        let falling = 1 << 5 | 1 << 15;
+        assert_eq!(sense_registers(rising, falling), [0xC0, 0x00, 0x08, 0x01]);
+    }
+
+    #[test]
+    fn misc_config_round_trips() {
+        let config = MiscConfig {
+            fade_mode_a: FadeMode::Logarithmic,
+            fade_mode_b: FadeMode::Linear,
+            led_clock_divider: 3,
+            nreset_function: NresetFunction::LedSync,
+            nint_autoclear: false,
+        };
+        assert_eq!(config.bits(), 0b0011_1101);
+        assert_eq!(MiscConfig::from_bits(config.bits()), config);
+    }
+
+    #[test]
+    fn coalesce_writes_changes_in_one_burst() {
+        let bus = MockBus::new();
+        let interface = bus.interface();
+        interface
+            .coalesce(|stage| {
+                stage.set_output::<0>()?;
+                stage.set_open_drain::<0>(true)?;
+                Ok(())
+            })
+            .unwrap();
+
+        // RegOpenDrainA to RegDirA, bridging the known registers between.
+        assert_eq!(
+            bus.take_writes(),
+            [(0x0B, vec![0x01, 0x00, 0x00, 0xFF, 0xFE])]
+        );
+        assert_eq!(bus.register(0x0F), 0xFE);
+    }
+
+    #[test]
+    fn coalesce_skips_known_values() {
+        let bus = MockBus::new();
+        let interface = bus.interface();
+        let set_output = |stage: &mut Stage<'_, MockBus>| stage.set_output::<3>();
+        interface.coalesce(set_output).unwrap();
+        let reads = bus.reads();
+        bus.take_writes();
+
+        interface.coalesce(set_output).unwrap();
+        assert_eq!(bus.take_writes(), []);
+        assert_eq!(bus.reads(), reads);
+    }
+
+    #[test]
+    fn failed_stage_writes_nothing() {
+        let bus = MockBus::new();
+        let interface = bus.interface();
+        let result = interface.coalesce(|stage| {
+            stage.set_output::<0>()?;
+            Err(Error::BusBusy)
+        });
+
+        assert!(matches!(result, Err(Error::BusBusy)));
+        assert_eq!(bus.take_writes(), []);
+        assert_eq!(bus.register(0x0F), 0xFF);
+    }
+
+    #[test]
+    fn failed_commit_forgets_the_shadow() {
+        let bus = MockBus::new();
+        let interface = bus.interface();
+        interface.coalesce(Stage::set_output::<0>).unwrap();
+        bus.take_writes();
+
+        bus.fail_next(1);
+        let result = interface.coalesce(Stage::set_output::<1>);
+        assert!(matches!(result, Err(Error::Io(_))));
+
+        // The chip changed behind the driver's back, which it must notice.
+        bus.set_register(0x0F, 0xF3);
+        let reads = bus.reads();
+        interface.coalesce(Stage::set_output::<1>).unwrap();
+        assert_eq!(bus.reads(), reads + 1);
+        assert_eq!(bus.take_writes(), [(0x0F, vec![0xF1])]);
+    }
+
+    #[test]
+    fn batch_defers_writes_until_flush() {
+        let bus = MockBus::new();
+        let interface = bus.interface();
+        interface.start_batch();
+        interface.coalesce(Stage::set_output::<8>).unwrap();
+        interface.set_pull_up::<9>(true).unwrap();
+        assert_eq!(bus.take_writes(), []);
+
+        interface.flush().unwrap();
+        assert_eq!(
+            bus.take_writes(),
+            [(
+                0x06,
+                vec![0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFE]
+            )]
+        );
+    }
 }
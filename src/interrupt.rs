@@ -0,0 +1,740 @@
+use embedded_hal::digital::InputPin;
+use portable_atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicU8, AtomicUsize, Ordering};
+
+use crate::{
+    error::{Error, NintError},
+    lock::BusLock,
+    states::InterruptOn,
+    Input, Interface, Pins, Sx1509,
+};
+
+/// The edges of an input that trigger an interrupt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Low to high transitions.
+    Rising = 0b01,
+    /// High to low transitions.
+    Falling = 0b10,
+    /// Both transitions.
+    Both = 0b11,
+}
+
+/// An edge seen on a pin by [`InterruptHandler::service`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinEvent {
+    /// The pin that triggered, where `0` is `a0` and `15` is `b7`.
+    pub pin: u8,
+    /// The edge the pin was listening for. With [`Edge::Both`], read the pin
+    /// to find out which edge it was.
+    pub edge: Edge,
+}
+
+impl Edge {
+    /// The edge for two bits of a sense register, or `None` if they are
+    /// zero.
+    pub(crate) fn from_bits(bits: u8) -> Option<Self> {
+        match bits & 0b11 {
+            0b01 => Some(Edge::Rising),
+            0b10 => Some(Edge::Falling),
+            0b11 => Some(Edge::Both),
+            _ => None,
+        }
+    }
+}
+
+impl PinEvent {
+    /// Pack the event into a byte, with the edge above the pin.
+    fn to_bits(self) -> u8 {
+        self.pin | (self.edge as u8) << 4
+    }
+
+    /// Unpack an event packed by [`to_bits`](Self::to_bits).
+    fn from_bits(bits: u8) -> Self {
+        Self {
+            pin: bits & 0x0F,
+            edge: Edge::from_bits(bits >> 4).unwrap_or(Edge::Both),
+        }
+    }
+}
+
+/// What the handler knows about a pin it listens to.
+#[derive(Clone, Copy, Default)]
+struct Slot {
+    edge: Option<Edge>,
+    callback: Option<fn(PinEvent)>,
+}
+
+/// Reads the interrupt source registers once per NINT assertion and hands the
+/// result out to per-pin [`PinFlag`]s, so many inputs can be watched without
+/// reading each of them over I2C. Get it from
+/// [`Sx1509::split`](crate::Sx1509::split).
+///
+/// ```rust
+/// # use sx1509_eh::{interrupt::Edge, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let Pins { a0, a1, interrupts, .. } = expander.split();
+/// let up = a0.into_input()?.pullup()?.enable_interrupt(Edge::Falling)?;
+/// let down = a1.into_input()?.pullup()?.enable_interrupt(Edge::Falling)?;
+/// let up_pressed = interrupts.listen(&up)?;
+/// let down_pressed = interrupts.listen(&down)?;
+///
+/// // When the MCU sees NINT go low:
+/// interrupts.service()?;
+/// if up_pressed.take() {
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct InterruptHandler<'a, I2C: BusLock> {
+    interface: &'a Interface<I2C>,
+    /// The pins being listened to, where bit 0 is `a0` and bit 15 is `b7`.
+    mask: AtomicU16,
+    /// The pins that have triggered since their flag was last taken.
+    flags: AtomicU16,
+    /// The pins whose edges are being counted.
+    counting: AtomicU16,
+    counts: [AtomicU32; 16],
+    /// Two copies of the slots, changed one after the other. A change only
+    /// ever holds one of them, so [`service`](Self::service) can always read
+    /// the other without waiting, even when it interrupts the change.
+    slots: [spin::RwLock<[Slot; 16]>; 2],
+}
+
+impl<'a, I2C: BusLock> InterruptHandler<'a, I2C> {
+    pub(crate) fn new(interface: &'a Interface<I2C>) -> Self {
+        Self {
+            interface,
+            mask: AtomicU16::new(0),
+            flags: AtomicU16::new(0),
+            counting: AtomicU16::new(0),
+            counts: [const { AtomicU32::new(0) }; 16],
+            slots: [
+                spin::RwLock::new([Slot::default(); 16]),
+                spin::RwLock::new([Slot::default(); 16]),
+            ],
+        }
+    }
+
+    /// The pins being listened to, where bit 0 is `a0` and bit 15 is `b7`.
+    #[must_use]
+    pub fn mask(&self) -> u16 {
+        self.mask.load(Ordering::Relaxed)
+    }
+
+    /// Call `callback` from [`service`](Self::service) whenever `input`
+    /// triggers, replacing any previous callback for it. The pin still needs
+    /// to be [listened to](Self::listen).
+    ///
+    /// The callback runs in whatever context calls `service`, so keep it
+    /// short if that is an interrupt handler.
+    pub fn on_event<const PIN: u8, S, D>(
+        &self,
+        _input: &Input<'_, PIN, I2C, S, D, InterruptOn>,
+        callback: fn(PinEvent),
+    ) {
+        self.update_slot::<PIN>(|slot| slot.callback = Some(callback));
+    }
+
+    /// Stop watching `input`. Its flag is no longer raised, and its interrupt
+    /// is left for something else to clear. The pin still asserts NINT until
+    /// its interrupt is [disabled](Input::disable_interrupt).
+    pub fn unlisten<const PIN: u8, S, D>(&self, _input: &Input<'_, PIN, I2C, S, D, InterruptOn>) {
+        self.mask.fetch_and(!(1 << PIN), Ordering::Relaxed);
+    }
+
+    /// Stop counting the edges of `input`. Its count is kept until
+    /// [taken](Self::take_count).
+    pub fn stop_counting<const PIN: u8, S, D>(
+        &self,
+        _input: &Input<'_, PIN, I2C, S, D, InterruptOn>,
+    ) {
+        self.counting.fetch_and(!(1 << PIN), Ordering::Relaxed);
+    }
+
+    /// The number of edges counted on `input` since it was last taken,
+    /// resetting it to zero. Pins that aren't being
+    /// [counted](Self::count_edges) return zero.
+    #[must_use]
+    pub fn take_count<const PIN: u8, S, D>(
+        &self,
+        _input: &Input<'_, PIN, I2C, S, D, InterruptOn>,
+    ) -> u32 {
+        self.counts[usize::from(PIN)].swap(0, Ordering::Relaxed)
+    }
+
+    /// Remove the callback for `input`, if any.
+    pub fn remove_callback<const PIN: u8, S, D>(
+        &self,
+        _input: &Input<'_, PIN, I2C, S, D, InterruptOn>,
+    ) {
+        self.update_slot::<PIN>(|slot| slot.callback = None);
+    }
+
+    fn update_slot<const PIN: u8>(&self, update: impl Fn(&mut Slot)) {
+        for copy in &self.slots {
+            update(&mut copy.write()[usize::from(PIN)]);
+        }
+    }
+
+    /// A copy of the slots, read from whichever copy isn't being changed.
+    fn read_slots(&self) -> [Slot; 16] {
+        loop {
+            // Only another change on a different core can hold both.
+            if let Some(slots) = self.slots.iter().find_map(spin::RwLock::try_read) {
+                return *slots;
+            }
+            core::hint::spin_loop();
+        }
+    }
+}
+
+impl<I2C, E> InterruptHandler<'_, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Start watching `input`, and return a flag that is raised whenever
+    /// [`service`](Self::service) sees it trigger. The input's edge is read
+    /// back from the chip, for its [`PinEvent`]s.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn listen<const PIN: u8, S, D>(
+        &self,
+        input: &Input<'_, PIN, I2C, S, D, InterruptOn>,
+    ) -> Result<PinFlag<'_>, Error<E>> {
+        let edge = input.interface.interrupt_edge::<PIN>()?;
+        self.update_slot::<PIN>(|slot| slot.edge = edge);
+        self.mask.fetch_or(1 << PIN, Ordering::Relaxed);
+        self.flags.fetch_and(!(1 << PIN), Ordering::Relaxed);
+        Ok(PinFlag {
+            flags: &self.flags,
+            pin: PIN,
+        })
+    }
+
+    /// Count the edges of `input` every time [`service`](Self::service) runs,
+    /// starting from zero. The chip only latches one edge per pin between
+    /// services, so faster pulses are undercounted. Counting is independent
+    /// of [listening](Self::listen), and the pin's interrupt is cleared by
+    /// the handler either way.
+    pub fn count_edges<const PIN: u8, S, D>(
+        &self,
+        _input: &Input<'_, PIN, I2C, S, D, InterruptOn>,
+    ) {
+        self.counts[usize::from(PIN)].store(0, Ordering::Relaxed);
+        self.counting.fetch_or(1 << PIN, Ordering::Relaxed);
+    }
+
+    /// Handle a NINT assertion: read the event status of both banks in one
+    /// transaction, raise the flags of the pins being listened to, bump the
+    /// counts of the pins being counted, clear them on the chip and run their
+    /// [callbacks](Self::on_event). Returns the listened to pins that
+    /// triggered, where bit 0 is `a0` and bit 15 is `b7`. Sources not being
+    /// listened to or counted, such as the keypad, are left for their own
+    /// handlers.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn service(&self) -> Result<u16, Error<E>> {
+        self.service_with(|_| {})
+    }
+
+    /// Like [`service`](Self::service), but also push an event for every
+    /// triggered pin onto `queue`, to be handled later outside of interrupt
+    /// context. Events that don't fit are dropped and counted, see
+    /// [`EventQueue::take_dropped`].
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn service_into<const N: usize>(&self, queue: &EventQueue<N>) -> Result<u16, Error<E>> {
+        self.service_with(|event| {
+            // A full queue counts the event as dropped.
+            let _ = queue.push(event);
+        })
+    }
+
+    fn service_with(&self, mut on_event: impl FnMut(PinEvent)) -> Result<u16, Error<E>> {
+        let events = self.interface.event_status()?;
+        let triggered = events & self.mask();
+        let counted = events & self.counting.load(Ordering::Relaxed);
+        if triggered | counted != 0 {
+            self.interface.clear_interrupts(triggered | counted)?;
+        }
+
+        for pin in (0..16).filter(|pin| counted & (1 << pin) != 0) {
+            self.counts[pin].fetch_add(1, Ordering::Relaxed);
+        }
+
+        if triggered != 0 {
+            self.flags.fetch_or(triggered, Ordering::Relaxed);
+
+            // Copy the slots out so callbacks can use the handler.
+            let slots = self.read_slots();
+            for pin in (0..16).filter(|pin| triggered & (1 << pin) != 0) {
+                let slot = slots[usize::from(pin)];
+                let Some(edge) = slot.edge else {
+                    continue;
+                };
+
+                let event = PinEvent { pin, edge };
+                on_event(event);
+                if let Some(callback) = slot.callback {
+                    callback(event);
+                }
+            }
+        }
+        Ok(triggered)
+    }
+}
+
+/// A flag raised when [`InterruptHandler::service`] sees a pin trigger.
+/// Reading it doesn't touch the bus.
+#[derive(Clone, Copy)]
+pub struct PinFlag<'h> {
+    flags: &'h AtomicU16,
+    pin: u8,
+}
+
+impl PinFlag<'_> {
+    /// The pin this flag belongs to, where `0` is `a0` and `15` is `b7`.
+    #[must_use]
+    pub fn pin(&self) -> u8 {
+        self.pin
+    }
+
+    /// Whether the pin has triggered since the flag was last taken.
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        self.flags.load(Ordering::Relaxed) & (1 << self.pin) != 0
+    }
+
+    /// Lower the flag, returning whether it was raised.
+    #[must_use]
+    pub fn take(&self) -> bool {
+        self.flags.fetch_and(!(1 << self.pin), Ordering::Relaxed) & (1 << self.pin) != 0
+    }
+}
+
+/// The pins with a pending interrupt, from
+/// [`Sx1509::pending_interrupts`](crate::Sx1509::pending_interrupts).
+/// Iterating over it yields the pin numbers in order, where `0` is `a0` and
+/// `15` is `b7`. It can also be made from any mask, such as the one returned
+/// by [`Sx1509::service_nint`](crate::Sx1509::service_nint).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PendingInterrupts {
+    bits: u16,
+}
+
+impl PendingInterrupts {
+    /// Whether `pin` has a pending interrupt.
+    #[must_use]
+    pub fn contains(&self, pin: u8) -> bool {
+        pin < 16 && self.bits & (1 << pin) != 0
+    }
+}
+
+impl From<u16> for PendingInterrupts {
+    fn from(bits: u16) -> Self {
+        Self { bits }
+    }
+}
+
+impl Iterator for PendingInterrupts {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bits == 0 {
+            return None;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let pin = self.bits.trailing_zeros() as u8;
+        self.bits &= !(1 << pin);
+        Some(pin)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for PendingInterrupts {}
+
+/// A fixed-capacity queue of [`PinEvent`]s, filled by
+/// [`InterruptHandler::service_into`] and drained by the application, so
+/// that events can be handled outside of interrupt context. It holds up to
+/// `N` events and needs no allocator.
+///
+/// ```rust
+/// # use sx1509_eh::{interrupt::EventQueue, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { interrupts, .. } = expander.split();
+/// static EVENTS: EventQueue<16> = EventQueue::new();
+///
+/// // When the MCU sees NINT go low:
+/// interrupts.service_into(&EVENTS)?;
+///
+/// // Later, in the main loop:
+/// while let Some(event) = EVENTS.pop() {
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct EventQueue<const N: usize> {
+    /// The events, packed by [`PinEvent::to_bits`].
+    events: [AtomicU8; N],
+    /// The index of the oldest event. Both indices count up to `2 * N`, so
+    /// that a full queue can be told apart from an empty one.
+    head: AtomicUsize,
+    /// The index after the newest event.
+    tail: AtomicUsize,
+    /// Set while an event is being pushed. It is never waited on, so an
+    /// interrupt can't deadlock against the code it interrupted.
+    pushing: AtomicBool,
+    /// Set while an event is being popped.
+    popping: AtomicBool,
+    dropped: AtomicU32,
+}
+
+impl<const N: usize> EventQueue<N> {
+    /// Create an empty queue.
+    #[must_use]
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "an event queue must hold at least one event") };
+        Self {
+            events: [const { AtomicU8::new(0) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            pushing: AtomicBool::new(false),
+            popping: AtomicBool::new(false),
+            dropped: AtomicU32::new(0),
+        }
+    }
+
+    /// Add an event to the back of the queue. This never blocks, so it is
+    /// safe to call from an interrupt handler.
+    ///
+    /// # Errors
+    /// Returns the event back if the queue is full, or if another push is in
+    /// progress, such as one interrupted by this call. It is counted as
+    /// [dropped](Self::take_dropped).
+    pub fn push(&self, event: PinEvent) -> Result<(), PinEvent> {
+        if self.pushing.swap(true, Ordering::Acquire) {
+            self.count_dropped();
+            return Err(event);
+        }
+
+        let tail = self.tail.load(Ordering::Relaxed);
+        let full = Self::distance(self.head.load(Ordering::Acquire), tail) == N;
+        if !full {
+            self.events[tail % N].store(event.to_bits(), Ordering::Relaxed);
+            self.tail.store((tail + 1) % (2 * N), Ordering::Release);
+        }
+        self.pushing.store(false, Ordering::Release);
+
+        if full {
+            self.count_dropped();
+            return Err(event);
+        }
+        Ok(())
+    }
+
+    /// Take the oldest event from the queue, if any. This never blocks, and
+    /// returns `None` if another pop is in progress.
+    pub fn pop(&self) -> Option<PinEvent> {
+        if self.popping.swap(true, Ordering::Acquire) {
+            return None;
+        }
+
+        let head = self.head.load(Ordering::Relaxed);
+        let event = (head != self.tail.load(Ordering::Acquire)).then(|| {
+            let bits = self.events[head % N].load(Ordering::Relaxed);
+            self.head.store((head + 1) % (2 * N), Ordering::Release);
+            PinEvent::from_bits(bits)
+        });
+        self.popping.store(false, Ordering::Release);
+        event
+    }
+
+    /// The number of events in the queue.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        Self::distance(head, self.tail.load(Ordering::Acquire)).min(N)
+    }
+
+    /// Whether the queue is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many events have been dropped because the queue was full, since
+    /// the count was last taken. Resets the count.
+    pub fn take_dropped(&self) -> u32 {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    fn count_dropped(&self) {
+        // Saturate rather than wrap, an `Err` means the count is maxed out.
+        let _ = self
+            .dropped
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |dropped| {
+                dropped.checked_add(1)
+            });
+    }
+
+    /// The number of events between two indices.
+    fn distance(head: usize, tail: usize) -> usize {
+        (tail + 2 * N - head) % (2 * N)
+    }
+}
+
+impl<const N: usize> Default for EventQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An [`Sx1509`] bundled with the MCU input wired to its NINT pin, so the
+/// expander only needs to be read when it has actually flagged an interrupt.
+///
+/// ```rust
+/// # use sx1509_eh::{interrupt::Sx1509WithInt, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, nint_pin: impl embedded_hal::digital::InputPin) -> Result<(), Failed> {
+/// let mut expander = Sx1509WithInt::new(Sx1509::new(i2c, 0x3E)?, nint_pin);
+/// let (pins, mut nint) = expander.split();
+///
+/// loop {
+///     if let Some(triggered) = nint.poll_if_interrupted()? {
+///         // ...
+///     }
+/// }
+/// # }
+/// ```
+pub struct Sx1509WithInt<I2C: BusLock, NINT> {
+    expander: Sx1509<I2C>,
+    nint: NINT,
+}
+
+impl<I2C, E, NINT> Sx1509WithInt<I2C, NINT>
+where
+    I2C: BusLock<Error = E>,
+    NINT: InputPin,
+{
+    /// Bundle `expander` with the MCU input connected to its NINT pin.
+    pub fn new(expander: Sx1509<I2C>, nint: NINT) -> Self {
+        Self { expander, nint }
+    }
+
+    /// The expander, for chip-level configuration.
+    pub fn expander(&mut self) -> &mut Sx1509<I2C> {
+        &mut self.expander
+    }
+
+    /// Take the expander and NINT pin back out.
+    pub fn release(self) -> (Sx1509<I2C>, NINT) {
+        (self.expander, self.nint)
+    }
+
+    /// Whether NINT is asserted.
+    ///
+    /// # Errors
+    /// This function will return an error if reading the NINT pin fails.
+    pub fn is_interrupted(&mut self) -> Result<bool, NINT::Error> {
+        self.nint.is_low()
+    }
+
+    /// If NINT is asserted, read and clear the interrupts like
+    /// [`Sx1509::service_nint`] and return the pins that triggered. Otherwise
+    /// return `None` without touching the bus.
+    ///
+    /// # Errors
+    /// This function will return an error if reading the NINT pin or
+    /// communication with I2C fails.
+    pub fn poll_if_interrupted(&mut self) -> Result<Option<u16>, NintError<E, NINT::Error>> {
+        poll_if_interrupted(&self.expander.interface, &mut self.nint)
+    }
+
+    /// Split the expander into individual pins, like [`Sx1509::split`], and
+    /// keep a handle on NINT that can be polled while the pins exist.
+    pub fn split(&mut self) -> (Pins<'_, I2C>, Nint<'_, I2C, NINT>) {
+        let interface = &self.expander.interface;
+        (
+            Pins::new(interface),
+            Nint {
+                interface,
+                pin: &mut self.nint,
+            },
+        )
+    }
+}
+
+/// The NINT pin of a split [`Sx1509WithInt`].
+pub struct Nint<'a, I2C: BusLock, NINT> {
+    interface: &'a Interface<I2C>,
+    pin: &'a mut NINT,
+}
+
+impl<I2C, E, NINT> Nint<'_, I2C, NINT>
+where
+    I2C: BusLock<Error = E>,
+    NINT: InputPin,
+{
+    /// Whether NINT is asserted.
+    ///
+    /// # Errors
+    /// This function will return an error if reading the NINT pin fails.
+    pub fn is_interrupted(&mut self) -> Result<bool, NINT::Error> {
+        self.pin.is_low()
+    }
+
+    /// See [`Sx1509WithInt::poll_if_interrupted`].
+    ///
+    /// # Errors
+    /// This function will return an error if reading the NINT pin or
+    /// communication with I2C fails.
+    pub fn poll_if_interrupted(&mut self) -> Result<Option<u16>, NintError<E, NINT::Error>> {
+        poll_if_interrupted(self.interface, self.pin)
+    }
+}
+
+fn poll_if_interrupted<I2C, E, NINT>(
+    interface: &Interface<I2C>,
+    nint: &mut NINT,
+) -> Result<Option<u16>, NintError<E, NINT::Error>>
+where
+    I2C: BusLock<Error = E>,
+    NINT: InputPin,
+{
+    if nint.is_high().map_err(NintError::Pin)? {
+        return Ok(None);
+    }
+
+    let triggered = interface.interrupt_source()?;
+    if triggered != 0 {
+        interface.clear_interrupts(triggered)?;
+    }
+    Ok(Some(triggered))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use portable_atomic::AtomicU8;
+
+    use super::*;
+    use crate::{mock::MockBus, reg::Register};
+
+    fn event(pin: u8, edge: Edge) -> PinEvent {
+        PinEvent { pin, edge }
+    }
+
+    #[test]
+    fn pin_events_pack_into_a_byte() {
+        for pin in 0..16 {
+            for edge in [Edge::Rising, Edge::Falling, Edge::Both] {
+                assert_eq!(
+                    PinEvent::from_bits(event(pin, edge).to_bits()),
+                    event(pin, edge)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn queue_is_first_in_first_out() {
+        let queue = EventQueue::<4>::new();
+        assert!(queue.is_empty());
+        queue.push(event(1, Edge::Rising)).unwrap();
+        queue.push(event(2, Edge::Falling)).unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pop(), Some(event(1, Edge::Rising)));
+        assert_eq!(queue.pop(), Some(event(2, Edge::Falling)));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn full_queue_drops_and_counts() {
+        let queue = EventQueue::<2>::new();
+        queue.push(event(0, Edge::Both)).unwrap();
+        queue.push(event(1, Edge::Both)).unwrap();
+        assert_eq!(queue.push(event(2, Edge::Both)), Err(event(2, Edge::Both)));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.take_dropped(), 1);
+        assert_eq!(queue.take_dropped(), 0);
+    }
+
+    #[test]
+    fn queue_wraps_around() {
+        let queue = EventQueue::<3>::new();
+        for pin in 0..10 {
+            queue.push(event(pin, Edge::Falling)).unwrap();
+            queue.push(event(pin + 1, Edge::Rising)).unwrap();
+            assert_eq!(queue.len(), 2);
+            assert_eq!(queue.pop(), Some(event(pin, Edge::Falling)));
+            assert_eq!(queue.pop(), Some(event(pin + 1, Edge::Rising)));
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn pending_interrupts_iterate_in_order() {
+        let pending = PendingInterrupts::from(1 << 15 | 1 << 3 | 1 << 0);
+        assert!(pending.contains(3));
+        assert!(!pending.contains(4));
+        assert!(!pending.contains(16));
+        assert_eq!(pending.len(), 3);
+        assert_eq!(pending.collect::<Vec<_>>(), [0, 3, 15]);
+    }
+
+    static LAST_PIN: AtomicU8 = AtomicU8::new(u8::MAX);
+
+    #[test]
+    fn service_raises_flags_and_runs_callbacks() {
+        let bus = MockBus::new();
+        let mut expander = Sx1509::new(bus.clone(), 0x3E).unwrap();
+        let Pins { a2, interrupts, .. } = expander.split();
+        let input = a2
+            .into_input()
+            .unwrap()
+            .enable_interrupt(Edge::Falling)
+            .unwrap();
+        let flag = interrupts.listen(&input).unwrap();
+        interrupts.on_event(&input, |event| LAST_PIN.store(event.pin, Ordering::Relaxed));
+        let queue = EventQueue::<4>::new();
+
+        bus.set_register(Register::RegEventStatusA as u8, 1 << 2);
+        assert_eq!(interrupts.service_into(&queue).unwrap(), 1 << 2);
+        assert!(flag.take());
+        assert!(!flag.is_set());
+        assert_eq!(LAST_PIN.load(Ordering::Relaxed), 2);
+        assert_eq!(queue.pop(), Some(event(2, Edge::Falling)));
+        assert_eq!(bus.register(Register::RegEventStatusA as u8), 0);
+
+        interrupts.unlisten(&input);
+        bus.set_register(Register::RegEventStatusA as u8, 1 << 2);
+        assert_eq!(interrupts.service().unwrap(), 0);
+        assert!(!flag.is_set());
+    }
+}
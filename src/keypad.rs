@@ -0,0 +1,1205 @@
+use core::time::Duration;
+
+use crate::{
+    error::{Error, ModeChange},
+    interface::Bank,
+    lock::BusLock,
+    time::IntoDuration,
+    DebounceTime, Interface, Pin,
+};
+
+/// How long the keypad engine spends scanning each row. This must be longer
+/// than the debounce time. The durations are for the internal 2MHz oscillator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScanTime {
+    /// 1ms
+    #[default]
+    Ms1 = 0b000,
+    /// 2ms
+    Ms2 = 0b001,
+    /// 4ms
+    Ms4 = 0b010,
+    /// 8ms
+    Ms8 = 0b011,
+    /// 16ms
+    Ms16 = 0b100,
+    /// 32ms
+    Ms32 = 0b101,
+    /// 64ms
+    Ms64 = 0b110,
+    /// 128ms
+    Ms128 = 0b111,
+}
+
+impl ScanTime {
+    const ALL: [Self; 8] = [
+        Self::Ms1,
+        Self::Ms2,
+        Self::Ms4,
+        Self::Ms8,
+        Self::Ms16,
+        Self::Ms32,
+        Self::Ms64,
+        Self::Ms128,
+    ];
+
+    /// The scan time per row, with the internal 2MHz oscillator.
+    #[must_use]
+    pub const fn duration(self) -> Duration {
+        Duration::from_millis(1 << self as u8)
+    }
+
+    /// The scan time per row with the chip clocked at `oscillator_hz`. The
+    /// shortest time is 2000 clock cycles.
+    ///
+    /// # Panics
+    /// This function will panic if `oscillator_hz` is zero.
+    #[must_use]
+    pub fn duration_at(self, oscillator_hz: u32) -> Duration {
+        let cycles = 2000_u64 << self as u8;
+        Duration::from_nanos(cycles * 1_000_000_000 / u64::from(oscillator_hz))
+    }
+
+    /// The scan time closest to `target` with the chip clocked at
+    /// `oscillator_hz`.
+    ///
+    /// # Panics
+    /// This function will panic if `oscillator_hz` is zero.
+    #[must_use]
+    pub fn from_duration_at(target: impl IntoDuration, oscillator_hz: u32) -> Self {
+        let target = target.into_duration();
+        let mut nearest = Self::ALL[0];
+        for time in Self::ALL {
+            if time.duration_at(oscillator_hz).abs_diff(target)
+                < nearest.duration_at(oscillator_hz).abs_diff(target)
+            {
+                nearest = time;
+            }
+        }
+        nearest
+    }
+}
+
+/// How long the keypad engine waits without a key press before going to
+/// sleep. While asleep the rows aren't scanned, saving power, and the engine
+/// wakes up as soon as a key is pressed. The durations are for the internal
+/// 2MHz oscillator.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SleepTime {
+    /// Never sleep.
+    #[default]
+    Off = 0b000,
+    /// 128ms
+    Ms128 = 0b001,
+    /// 256ms
+    Ms256 = 0b010,
+    /// 512ms
+    Ms512 = 0b011,
+    /// 1s
+    S1 = 0b100,
+    /// 2s
+    S2 = 0b101,
+    /// 4s
+    S4 = 0b110,
+    /// 8s
+    S8 = 0b111,
+}
+
+impl SleepTime {
+    /// The time before the engine sleeps with the internal 2MHz oscillator, or
+    /// `None` if it never sleeps.
+    #[must_use]
+    pub const fn duration(self) -> Option<Duration> {
+        match self {
+            SleepTime::Off => None,
+            _ => Some(Duration::from_millis(64 << self as u8)),
+        }
+    }
+}
+
+/// A key on the keypad, identified by its row and column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    /// The row of the key, `0` being the row on `a0`.
+    pub row: u8,
+    /// The column of the key, `0` being the column on `b0`.
+    pub col: u8,
+}
+
+/// Maps keys to application level values, such as characters or key codes.
+///
+/// ```rust
+/// # use sx1509_eh::{keypad::{Keymap, Keypad}, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { a0, a1, a2, a3, b0, b1, b2, .. } = expander.split();
+/// # let mut keypad: Keypad<_, 4, 3> = Keypad::new((a0, a1, a2, a3), (b0, b1, b2))?;
+/// const KEYMAP: Keymap<char, 4, 3> = Keymap::new([
+///     ['1', '2', '3'],
+///     ['4', '5', '6'],
+///     ['7', '8', '9'],
+///     ['*', '0', '#'],
+/// ]);
+///
+/// if let Some(key) = keypad.read_mapped(&KEYMAP)? {
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keymap<T, const ROWS: usize, const COLS: usize> {
+    keys: [[T; COLS]; ROWS],
+}
+
+impl<T, const ROWS: usize, const COLS: usize> Keymap<T, ROWS, COLS> {
+    /// Create a keymap from a table of values, indexed by row then column.
+    #[must_use]
+    pub const fn new(keys: [[T; COLS]; ROWS]) -> Self {
+        Self { keys }
+    }
+
+    /// The value of `key`, or `None` if it is outside the keymap.
+    #[must_use]
+    pub fn get(&self, key: KeyEvent) -> Option<&T> {
+        self.keys
+            .get(usize::from(key.row))?
+            .get(usize::from(key.col))
+    }
+}
+
+impl KeyEvent {
+    fn from_index(index: u32) -> Self {
+        #[allow(clippy::cast_possible_truncation)]
+        Self {
+            row: (index / 8) as u8,
+            col: (index % 8) as u8,
+        }
+    }
+
+    fn bit(self) -> u64 {
+        1 << (self.row * 8 + self.col)
+    }
+}
+
+/// A change in the state of a key, reported by [`Keypad::poll`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeypadEvent {
+    /// The key was pressed.
+    Pressed(KeyEvent),
+    /// The key was released.
+    Released(KeyEvent),
+}
+
+/// A set of keys that are pressed at the same time, from
+/// [`Keypad::read_keys`]. Iterating over it yields the keys in order of row,
+/// then column.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Keys {
+    /// One bit per key at `row * 8 + col`.
+    bits: u64,
+}
+
+impl Keys {
+    /// Whether `key` is in the set.
+    #[must_use]
+    pub fn contains(&self, key: KeyEvent) -> bool {
+        key.row < 8 && key.col < 8 && self.bits & key.bit() != 0
+    }
+
+    /// Whether the set is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+}
+
+impl Iterator for Keys {
+    type Item = KeyEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bits == 0 {
+            return None;
+        }
+
+        let key = KeyEvent::from_index(self.bits.trailing_zeros());
+        self.bits &= !key.bit();
+        Some(key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Keys {}
+
+/// The events since the previous [`Keypad::poll`] or
+/// [`MatrixScanner::scan`]. Releases are reported before presses.
+#[derive(Debug, Clone)]
+pub struct Events {
+    released: Keys,
+    pressed: Keys,
+}
+
+impl Events {
+    fn between(previous: u64, current: u64) -> Self {
+        Self {
+            released: Keys {
+                bits: previous & !current,
+            },
+            pressed: Keys {
+                bits: current & !previous,
+            },
+        }
+    }
+}
+
+impl Iterator for Events {
+    type Item = KeypadEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.released
+            .next()
+            .map(KeypadEvent::Released)
+            .or_else(|| self.pressed.next().map(KeypadEvent::Pressed))
+    }
+}
+
+/// What the keypad should do when the pressed keys are ambiguous.
+///
+/// In a matrix without a diode per key, pressing three keys that form an L
+/// shape makes the fourth corner of the rectangle look pressed too (a ghost
+/// key). More generally, whenever more than one row and more than one column
+/// are active, the actually pressed keys can't be told apart from ghosts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GhostPolicy {
+    /// Report every key that may be pressed, ghosts included, and flag the
+    /// read as [`ghosted`](Keypad::ghosted).
+    #[default]
+    Report,
+    /// Report no key at all while the combination is ambiguous, and flag the
+    /// read as [`ghosted`](Keypad::ghosted).
+    Suppress,
+}
+
+mod sealed {
+    pub trait Sealed<'a, I2C: crate::lock::BusLock> {
+        fn interface(&self) -> &'a crate::Interface<I2C>;
+
+        /// Whether every pin belongs to the expander behind `interface`.
+        fn belongs_to(&self, interface: &crate::Interface<I2C>) -> bool;
+    }
+
+    pub trait MatrixSealed<'a, I2C: crate::lock::BusLock> {
+        fn interface(&self) -> &'a crate::Interface<I2C>;
+
+        /// Whether every pin belongs to the expander behind `interface`.
+        fn belongs_to(&self, interface: &crate::Interface<I2C>) -> bool;
+    }
+}
+
+/// The rows of a keypad with `N` rows, see [`KeypadPins`].
+pub struct Rows<const N: u8>;
+
+/// The columns of a keypad with `N` columns, see [`KeypadPins`].
+pub struct Columns<const N: u8>;
+
+/// The pins used by a keypad of a given size. This is only implemented for
+/// [`Rows`] of 2 to 8 and [`Columns`] of 1 to 8, so a keypad of any other size
+/// fails to compile.
+pub trait KeypadPins<'a, I2C: BusLock> {
+    /// A tuple of the pins, starting from `a0` for rows and `b0` for columns.
+    type Pins: sealed::Sealed<'a, I2C>;
+}
+
+/// A tuple of pins that can be used as the `N` rows of a [`Keypad`]: 2 to 8
+/// consecutive bank A pins, starting from `a0`.
+pub trait RowPins<'a, I2C: BusLock, const N: u8>: sealed::Sealed<'a, I2C> {}
+
+/// A tuple of pins that can be used as the `N` columns of a [`Keypad`]: 1 to
+/// 8 consecutive bank B pins, starting from `b0`.
+pub trait ColumnPins<'a, I2C: BusLock, const N: u8>: sealed::Sealed<'a, I2C> {}
+
+macro_rules! keypad_pins {
+    ($size:ident, $trait:ident, $count:literal: $($pin:literal $field:tt),+) => {
+        impl<'a, I2C: BusLock> sealed::Sealed<'a, I2C> for ($(Pin<'a, $pin, I2C>,)+) {
+            fn interface(&self) -> &'a Interface<I2C> {
+                self.0.interface
+            }
+
+            fn belongs_to(&self, interface: &Interface<I2C>) -> bool {
+                $(core::ptr::eq(interface, self.$field.interface))&&+
+            }
+        }
+
+        impl<'a, I2C: BusLock> $trait<'a, I2C, $count> for ($(Pin<'a, $pin, I2C>,)+) {}
+
+        impl<'a, I2C: BusLock + 'a> KeypadPins<'a, I2C> for $size<$count> {
+            type Pins = ($(Pin<'a, $pin, I2C>,)+);
+        }
+    };
+}
+
+keypad_pins!(Rows, RowPins, 2: 0 0, 1 1);
+keypad_pins!(Rows, RowPins, 3: 0 0, 1 1, 2 2);
+keypad_pins!(Rows, RowPins, 4: 0 0, 1 1, 2 2, 3 3);
+keypad_pins!(Rows, RowPins, 5: 0 0, 1 1, 2 2, 3 3, 4 4);
+keypad_pins!(Rows, RowPins, 6: 0 0, 1 1, 2 2, 3 3, 4 4, 5 5);
+keypad_pins!(Rows, RowPins, 7: 0 0, 1 1, 2 2, 3 3, 4 4, 5 5, 6 6);
+keypad_pins!(Rows, RowPins, 8: 0 0, 1 1, 2 2, 3 3, 4 4, 5 5, 6 6, 7 7);
+
+keypad_pins!(Columns, ColumnPins, 1: 8 0);
+keypad_pins!(Columns, ColumnPins, 2: 8 0, 9 1);
+keypad_pins!(Columns, ColumnPins, 3: 8 0, 9 1, 10 2);
+keypad_pins!(Columns, ColumnPins, 4: 8 0, 9 1, 10 2, 11 3);
+keypad_pins!(Columns, ColumnPins, 5: 8 0, 9 1, 10 2, 11 3, 12 4);
+keypad_pins!(Columns, ColumnPins, 6: 8 0, 9 1, 10 2, 11 3, 12 4, 13 5);
+keypad_pins!(Columns, ColumnPins, 7: 8 0, 9 1, 10 2, 11 3, 12 4, 13 5, 14 6);
+keypad_pins!(Columns, ColumnPins, 8: 8 0, 9 1, 10 2, 11 3, 12 4, 13 5, 14 6, 15 7);
+
+/// The pins used as the rows of a keypad with `N` rows.
+pub type KeypadRows<'a, I2C, const N: u8> = <Rows<N> as KeypadPins<'a, I2C>>::Pins;
+
+/// The pins used as the columns of a keypad with `N` columns.
+pub type KeypadColumns<'a, I2C, const N: u8> = <Columns<N> as KeypadPins<'a, I2C>>::Pins;
+
+/// The SX1509's keypad scanning engine, scanning `ROWS` rows and `COLS`
+/// columns.
+///
+/// The engine scans a matrix of up to 8 rows and 8 columns. Rows are always
+/// bank A pins starting from `a0`, and columns are always bank B pins
+/// starting from `b0`. Creating a keypad consumes those pins, so they can't
+/// be used as GPIO at the same time. The size is inferred from the pins, and
+/// unsupported sizes fail to compile.
+///
+/// Only the pins of the rows and columns in use are taken, and the keypad
+/// only ever changes the configuration of those pins. With a smaller matrix,
+/// the rest of each bank can still be used as inputs, outputs or LEDs
+/// alongside the keypad. Note that the debounce time is shared by every pin
+/// on the chip, and NINT is shared with any other interrupt sources.
+///
+/// ```rust
+/// # use sx1509_eh::{keypad::Keypad, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let Pins { a0, a1, a2, a3, a4, b0, b1, b2, b3, .. } = expander.split();
+/// let mut keypad: Keypad<_, 4, 3> = Keypad::new((a0, a1, a2, a3), (b0, b1, b2)).unwrap();
+///
+/// // The leftover pins are still ordinary GPIO.
+/// let mut backlight = a4.into_output().unwrap();
+/// let button = b3.into_input().unwrap();
+/// # Ok(())
+/// # }
+/// ```
+pub struct Keypad<'a, I2C: BusLock, const ROWS: u8, const COLS: u8>
+where
+    Rows<ROWS>: KeypadPins<'a, I2C>,
+    Columns<COLS>: KeypadPins<'a, I2C>,
+{
+    interface: &'a Interface<I2C>,
+    rows: KeypadRows<'a, I2C, ROWS>,
+    columns: KeypadColumns<'a, I2C, COLS>,
+    /// The keys pressed at the last [`poll`](Self::poll), one bit per key at
+    /// `row * 8 + col`.
+    pressed: u64,
+    ghost_policy: GhostPolicy,
+    /// Whether the last read saw an ambiguous combination of keys.
+    ghosted: bool,
+    scan_time: ScanTime,
+    sleep_time: SleepTime,
+}
+
+impl<'a, I2C, E, const ROWS: u8, const COLS: u8> Keypad<'a, I2C, ROWS, COLS>
+where
+    I2C: BusLock<Error = E>,
+    Rows<ROWS>: KeypadPins<'a, I2C>,
+    Columns<COLS>: KeypadPins<'a, I2C>,
+{
+    /// Start the keypad scanning engine on the given row and column pins.
+    /// The rows are driven open-drain and the columns become debounced
+    /// inputs with pull-ups.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](Error::OscillatorOff) if the oscillator isn't
+    /// running, as the engine needs it. If an error occurs, the pins can be
+    /// extracted from the [`ModeChange`], although they may be partially
+    /// configured.
+    ///
+    /// # Panics
+    /// All of the rows and columns must belong to the same expander. This
+    /// function panics if they don't.
+    pub fn new<R, C>(rows: R, columns: C) -> Result<Self, ModeChange<Error<E>, (R, C)>>
+    where
+        R: RowPins<'a, I2C, ROWS> + Into<KeypadRows<'a, I2C, ROWS>>,
+        C: ColumnPins<'a, I2C, COLS> + Into<KeypadColumns<'a, I2C, COLS>>,
+    {
+        let interface = rows.interface();
+        assert!(
+            rows.belongs_to(interface) && columns.belongs_to(interface),
+            "the rows and columns of a keypad must belong to the same expander"
+        );
+        match interface.enable_keypad(ROWS, COLS) {
+            Ok(()) => Ok(Self {
+                interface,
+                rows: rows.into(),
+                columns: columns.into(),
+                pressed: 0,
+                ghost_policy: GhostPolicy::Report,
+                ghosted: false,
+                scan_time: ScanTime::Ms1,
+                sleep_time: SleepTime::Off,
+            }),
+            Err(error) => Err(ModeChange {
+                error,
+                pin: (rows, columns),
+            }),
+        }
+    }
+
+    /// Turn off the keypad scanning engine and return the row and column
+    /// pins, reset to unconfigured inputs.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the keypad can be extracted from the [`ModeChange`],
+    /// although the engine may be partially disabled.
+    pub fn release(
+        self,
+    ) -> Result<(KeypadRows<'a, I2C, ROWS>, KeypadColumns<'a, I2C, COLS>), ModeChange<Error<E>, Self>>
+    {
+        match self.interface.disable_keypad(ROWS, COLS) {
+            Ok(()) => Ok((self.rows, self.columns)),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+}
+
+impl<'a, I2C: BusLock, const ROWS: u8, const COLS: u8> Keypad<'a, I2C, ROWS, COLS>
+where
+    Rows<ROWS>: KeypadPins<'a, I2C>,
+    Columns<COLS>: KeypadPins<'a, I2C>,
+{
+    const ROW_MASK: u8 = u8::MAX >> (8 - ROWS);
+    const COLUMN_MASK: u8 = u8::MAX >> (8 - COLS);
+
+    /// The number of rows being scanned.
+    #[must_use]
+    pub fn rows(&self) -> u8 {
+        ROWS
+    }
+
+    /// The number of columns being scanned.
+    #[must_use]
+    pub fn columns(&self) -> u8 {
+        COLS
+    }
+
+    /// Set what to do when the pressed keys are ambiguous.
+    pub fn set_ghost_policy(&mut self, policy: GhostPolicy) {
+        self.ghost_policy = policy;
+    }
+
+    /// Whether the last read saw an ambiguous combination of keys, which may
+    /// include ghost keys. See [`GhostPolicy`].
+    #[must_use]
+    pub fn ghosted(&self) -> bool {
+        self.ghosted
+    }
+
+    /// The current scan time per row.
+    #[must_use]
+    pub fn scan_time(&self) -> ScanTime {
+        self.scan_time
+    }
+
+    /// The current sleep time.
+    #[must_use]
+    pub fn sleep_time(&self) -> SleepTime {
+        self.sleep_time
+    }
+
+    /// How long the engine takes to scan every row once at the current clock
+    /// frequency, or `None` if the oscillator is off. After waking from
+    /// sleep, the key data is only complete once a full scan has finished.
+    #[must_use]
+    pub fn scan_period(&self) -> Option<Duration> {
+        let oscillator_hz = self.interface.oscillator_hz();
+        (oscillator_hz != 0).then(|| self.scan_time.duration_at(oscillator_hz) * u32::from(ROWS))
+    }
+}
+
+impl<'a, I2C, E, const ROWS: u8, const COLS: u8> Keypad<'a, I2C, ROWS, COLS>
+where
+    I2C: BusLock<Error = E>,
+    Rows<ROWS>: KeypadPins<'a, I2C>,
+    Columns<COLS>: KeypadPins<'a, I2C>,
+{
+    /// Set how long the engine spends scanning each row. This must be longer
+    /// than the debounce time.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_scan_time(&mut self, scan_time: ScanTime) -> Result<(), Error<E>> {
+        self.interface.set_keypad_scan_time(scan_time)?;
+        self.scan_time = scan_time;
+        Ok(())
+    }
+
+    /// Set the scan time closest to `target` for the current clock frequency,
+    /// returning the actual scan time per row. This must be longer than the
+    /// debounce time.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](Error::OscillatorOff) if the oscillator isn't
+    /// running.
+    pub fn set_scan_duration(&mut self, target: impl IntoDuration) -> Result<Duration, Error<E>> {
+        let oscillator_hz = self.interface.oscillator_hz();
+        if oscillator_hz == 0 {
+            return Err(Error::OscillatorOff);
+        }
+
+        let scan_time = ScanTime::from_duration_at(target, oscillator_hz);
+        self.set_scan_time(scan_time)?;
+        Ok(scan_time.duration_at(oscillator_hz))
+    }
+
+    /// Set how long the engine waits without a key press before sleeping, or
+    /// [`SleepTime::Off`] to keep scanning forever, which is the default.
+    ///
+    /// While asleep the engine stops scanning and holds every row low, so a
+    /// press on any key pulls its column low and wakes it. If the keypad
+    /// interrupt is [enabled](Self::enable_interrupt), that press also
+    /// asserts NINT, which can wake a sleeping MCU. Scanning only restarts on
+    /// wake, so the key data may be empty or incomplete for up to one
+    /// [`scan_period`](Self::scan_period) afterwards. If
+    /// [`service`](Self::service) returns no events after a wake-up, poll
+    /// again once that time has passed.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_sleep_time(&mut self, sleep_time: SleepTime) -> Result<(), Error<E>> {
+        self.interface.set_keypad_sleep_time(sleep_time)?;
+        self.sleep_time = sleep_time;
+        Ok(())
+    }
+
+    /// Set the debounce time of the columns. This must be shorter than the
+    /// scan time. The debounce time is shared by every pin on the chip.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](Error::OscillatorOff) if the oscillator isn't
+    /// running.
+    pub fn set_debounce_time(&mut self, debounce_time: DebounceTime) -> Result<(), Error<E>> {
+        self.interface.set_debounce_time(debounce_time)
+    }
+
+    /// Read every currently pressed key. The engine reports the active rows
+    /// and columns, so several keys in the same row or column can be told
+    /// apart, allowing chords. Keys spread over several rows and columns are
+    /// ambiguous, see [`GhostPolicy`].
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn read_keys(&mut self) -> Result<Keys, Error<E>> {
+        let (columns, rows) = self.interface.key_data()?;
+        let columns = columns & Self::COLUMN_MASK;
+        let rows = rows & Self::ROW_MASK;
+
+        self.ghosted = rows.count_ones() > 1 && columns.count_ones() > 1;
+        if self.ghosted && self.ghost_policy == GhostPolicy::Suppress {
+            return Ok(Keys::default());
+        }
+
+        let bits = (0..8)
+            .filter(|row| rows & (1 << row) != 0)
+            .fold(0, |bits, row| bits | u64::from(columns) << (row * 8));
+        Ok(Keys { bits })
+    }
+
+    /// Read the currently pressed key, or `None` if no key is pressed. If
+    /// several keys are pressed, the one with the lowest row and column is
+    /// returned. Use [`read_keys`](Self::read_keys) to get all of them.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn read_key(&mut self) -> Result<Option<KeyEvent>, Error<E>> {
+        Ok(self.read_keys()?.next())
+    }
+
+    /// Read the keypad and report which keys were pressed or released since
+    /// the previous poll. The chip only reports the keys currently held, so
+    /// this needs to be called often enough not to miss short presses.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::{keypad::{Keypad, KeypadEvent}, Pins, Sx1509};
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// # let Pins { a0, a1, a2, a3, b0, b1, b2, .. } = expander.split();
+    /// # let mut keypad: Keypad<_, 4, 3> = Keypad::new((a0, a1, a2, a3), (b0, b1, b2))?;
+    /// for event in keypad.poll()? {
+    ///     match event {
+    ///         KeypadEvent::Pressed(key) => { /* ... */ }
+    ///         KeypadEvent::Released(key) => { /* ... */ }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn poll(&mut self) -> Result<Events, Error<E>> {
+        let pressed = self.read_keys()?.bits;
+        let events = Events::between(self.pressed, pressed);
+        self.pressed = pressed;
+        Ok(events)
+    }
+
+    /// Make a key press assert the NINT pin, so the keypad can be serviced from
+    /// an interrupt with [`service`](Self::service) instead of being polled.
+    /// This also works while the engine is asleep, see
+    /// [`set_sleep_time`](Self::set_sleep_time).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn enable_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.interface.set_keypad_interrupt(Self::COLUMN_MASK, true)
+    }
+
+    /// Stop key presses from asserting the NINT pin.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn disable_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.interface
+            .set_keypad_interrupt(Self::COLUMN_MASK, false)
+    }
+
+    /// Handle a NINT assertion caused by the keypad: read and decode the key
+    /// data like [`poll`](Self::poll), then clear the keypad's interrupt so
+    /// NINT is released. Call this when the MCU sees NINT go low (or from a
+    /// task woken by that interrupt).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn service(&mut self) -> Result<Events, Error<E>> {
+        let events = self.poll()?;
+        self.interface
+            .clear_bank_interrupts(Bank::B, Self::COLUMN_MASK)?;
+        Ok(events)
+    }
+
+    /// Read the currently pressed key and look it up in `keymap`. Returns
+    /// `None` if no key is pressed, or if the key isn't in the keymap.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn read_mapped<T: Copy, const R: usize, const C: usize>(
+        &mut self,
+        keymap: &Keymap<T, R, C>,
+    ) -> Result<Option<T>, Error<E>> {
+        Ok(self.read_key()?.and_then(|key| keymap.get(key)).copied())
+    }
+}
+
+/// A tuple of 1 to 8 pins, in any order and from either bank, used as the
+/// rows or columns of a [`MatrixScanner`].
+pub trait MatrixPins<'a, I2C: BusLock>: sealed::MatrixSealed<'a, I2C> {
+    /// The pin numbers in order, where `0` is `a0` and `15` is `b7`.
+    const PINS: &'static [u8];
+
+    /// A mask of the pins, where bit 0 is `a0` and bit 15 is `b7`.
+    #[must_use]
+    fn mask() -> u16 {
+        Self::PINS.iter().fold(0, |mask, pin| mask | 1 << pin)
+    }
+}
+
+macro_rules! matrix_pins {
+    ($($pin:ident $field:tt),+) => {
+        impl<'a, I2C: BusLock, $(const $pin: u8),+> sealed::MatrixSealed<'a, I2C> for ($(Pin<'a, $pin, I2C>,)+) {
+            fn interface(&self) -> &'a Interface<I2C> {
+                self.0.interface
+            }
+
+            fn belongs_to(&self, interface: &Interface<I2C>) -> bool {
+                $(core::ptr::eq(interface, self.$field.interface))&&+
+            }
+        }
+
+        impl<'a, I2C: BusLock, $(const $pin: u8),+> MatrixPins<'a, I2C> for ($(Pin<'a, $pin, I2C>,)+) {
+            const PINS: &'static [u8] = &[$($pin),+];
+        }
+    };
+}
+
+matrix_pins!(P0 0);
+matrix_pins!(P0 0, P1 1);
+matrix_pins!(P0 0, P1 1, P2 2);
+matrix_pins!(P0 0, P1 1, P2 2, P3 3);
+matrix_pins!(P0 0, P1 1, P2 2, P3 3, P4 4);
+matrix_pins!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+matrix_pins!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6);
+matrix_pins!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5, P6 6, P7 7);
+
+/// A key matrix scanned in software, for matrices that don't fit the
+/// [`Keypad`] engine's pin constraints. The rows and columns can be any pins,
+/// in any order, as long as there are at most 8 of each.
+///
+/// Each row is driven low in turn while the columns are read in bulk, so a
+/// scan costs a few I2C transactions per row. The keys are debounced in
+/// software: a change is only reported once the matrix has read the same for
+/// [`set_debounce_scans`](Self::set_debounce_scans) scans in a row. Unlike
+/// the engine, this can tell every combination of keys apart, as long as the
+/// matrix has a diode per key.
+///
+/// ```rust
+/// # use sx1509_eh::{keypad::MatrixScanner, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, delay: &mut impl embedded_hal::delay::DelayNs) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let Pins { a5, a6, b3, b4, b7, .. } = expander.split();
+/// let mut matrix = MatrixScanner::new((a5, a6), (b7, b3, b4)).unwrap();
+///
+/// loop {
+///     for event in matrix.scan()? {
+///         // ...
+///     }
+///     delay.delay_ms(5);
+/// }
+/// # }
+/// ```
+pub struct MatrixScanner<'a, I2C: BusLock, R, C> {
+    interface: &'a Interface<I2C>,
+    rows: R,
+    columns: C,
+    /// The debounced keys, one bit per key at `row * 8 + col`.
+    pressed: u64,
+    /// The keys seen by the last scan, before debouncing.
+    last_scan: u64,
+    /// How many scans in a row have matched `last_scan`.
+    stable_scans: u8,
+    debounce_scans: u8,
+}
+
+impl<'a, I2C, E, R, C> MatrixScanner<'a, I2C, R, C>
+where
+    I2C: BusLock<Error = E>,
+    R: MatrixPins<'a, I2C>,
+    C: MatrixPins<'a, I2C>,
+{
+    /// Set up the row pins as open-drain outputs and the column pins as
+    /// inputs with pull-ups. Keys are debounced over 2 scans by default.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the pins can be extracted from the [`ModeChange`],
+    /// although they may be partially configured.
+    ///
+    /// # Panics
+    /// All of the rows and columns must belong to the same expander. This
+    /// function panics if they don't.
+    pub fn new(rows: R, columns: C) -> Result<Self, ModeChange<Error<E>, (R, C)>> {
+        let interface = rows.interface();
+        assert!(
+            rows.belongs_to(interface) && columns.belongs_to(interface),
+            "the rows and columns of a matrix must belong to the same expander"
+        );
+        match interface.enable_matrix(R::mask(), C::mask()) {
+            Ok(()) => Ok(Self {
+                interface,
+                rows,
+                columns,
+                pressed: 0,
+                last_scan: 0,
+                stable_scans: 0,
+                debounce_scans: 2,
+            }),
+            Err(error) => Err(ModeChange {
+                error,
+                pin: (rows, columns),
+            }),
+        }
+    }
+
+    /// Return the row and column pins, reset to unconfigured inputs.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the scanner can be extracted from the
+    /// [`ModeChange`], although the pins may be partially reset.
+    pub fn release(self) -> Result<(R, C), ModeChange<Error<E>, Self>> {
+        match self.interface.disable_matrix(R::mask(), C::mask()) {
+            Ok(()) => Ok((self.rows, self.columns)),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Set how many scans in a row must agree before a change is reported.
+    /// `1` turns debouncing off. The debounce time is this many times the
+    /// interval between calls to [`scan`](Self::scan).
+    ///
+    /// # Panics
+    /// Panics if `scans` is `0`.
+    pub fn set_debounce_scans(&mut self, scans: u8) {
+        assert!(scans > 0, "at least one scan is needed");
+        self.debounce_scans = scans;
+    }
+
+    /// Scan the matrix once, without debouncing, and return every pressed
+    /// key.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn read_keys(&mut self) -> Result<Keys, Error<E>> {
+        let mut bits = 0;
+        for (row, &pin) in R::PINS.iter().enumerate() {
+            self.interface.set_data_mask(1 << pin, false)?;
+            let data = self.interface.get_data_mask();
+            self.interface.set_data_mask(1 << pin, true)?;
+
+            // Columns are pulled up, so a pressed key reads low.
+            let data = !data?;
+            for (col, &pin) in C::PINS.iter().enumerate() {
+                if data & (1 << pin) != 0 {
+                    bits |= 1 << (row * 8 + col);
+                }
+            }
+        }
+
+        Ok(Keys { bits })
+    }
+
+    /// Scan the matrix and report which keys were pressed or released since
+    /// the previous scan, after debouncing. Call this at a regular interval.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn scan(&mut self) -> Result<Events, Error<E>> {
+        let scanned = self.read_keys()?.bits;
+        if scanned == self.last_scan {
+            self.stable_scans = self.stable_scans.saturating_add(1);
+        } else {
+            self.last_scan = scanned;
+            self.stable_scans = 1;
+        }
+
+        let mut pressed = self.pressed;
+        if self.stable_scans >= self.debounce_scans {
+            pressed = scanned;
+        }
+
+        let events = Events::between(self.pressed, pressed);
+        self.pressed = pressed;
+        Ok(events)
+    }
+
+    /// The debounced keys as of the last [`scan`](Self::scan).
+    #[must_use]
+    pub fn pressed(&self) -> Keys {
+        Keys { bits: self.pressed }
+    }
+}
+
+/// A key event from [`KeyRepeat`], which adds long presses and auto-repeat to
+/// the presses and releases reported by the keypad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyAction {
+    /// The key was pressed.
+    Pressed(KeyEvent),
+    /// The key was released.
+    Released(KeyEvent),
+    /// The key has been held for the long press time.
+    LongPress(KeyEvent),
+    /// The key is being held, and should repeat.
+    Repeat(KeyEvent),
+}
+
+impl From<KeypadEvent> for KeyAction {
+    fn from(event: KeypadEvent) -> Self {
+        match event {
+            KeypadEvent::Pressed(key) => KeyAction::Pressed(key),
+            KeypadEvent::Released(key) => KeyAction::Released(key),
+        }
+    }
+}
+
+/// Synthesizes long press and auto-repeat events from keypad presses and
+/// releases. Only the most recently pressed key repeats, like on a computer
+/// keyboard.
+///
+/// Time is supplied by the caller as a monotonic timestamp, from any epoch,
+/// so this works with whatever time source the application has.
+///
+/// ```rust
+/// # use sx1509_eh::{keypad::{KeyRepeat, Keypad}, Pins, Sx1509};
+/// # use core::time::Duration;
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, now: impl Fn() -> Duration) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { a0, a1, a2, a3, b0, b1, b2, .. } = expander.split();
+/// # let mut keypad: Keypad<_, 4, 3> = Keypad::new((a0, a1, a2, a3), (b0, b1, b2))?;
+/// let mut repeat = KeyRepeat::new()
+///     .long_press(Duration::from_secs(1))
+///     .auto_repeat(Duration::from_millis(500), Duration::from_millis(100));
+///
+/// loop {
+///     for action in repeat.update(keypad.poll()?, now()) {
+///         // ...
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyRepeat {
+    long_press: Option<Duration>,
+    auto_repeat: Option<(Duration, Duration)>,
+    held: Option<Held>,
+}
+
+#[derive(Debug, Clone)]
+struct Held {
+    key: KeyEvent,
+    since: Duration,
+    next_repeat: Duration,
+    long_pressed: bool,
+}
+
+impl KeyRepeat {
+    /// Create a `KeyRepeat` with long presses and auto-repeat both disabled.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            long_press: None,
+            auto_repeat: None,
+            held: None,
+        }
+    }
+
+    /// Report a [`KeyAction::LongPress`] once a key has been held for `time`.
+    #[must_use]
+    pub const fn long_press(mut self, time: Duration) -> Self {
+        self.long_press = Some(time);
+        self
+    }
+
+    /// Report a [`KeyAction::Repeat`] once a key has been held for `delay`,
+    /// and then every `interval` after that.
+    #[must_use]
+    pub const fn auto_repeat(mut self, delay: Duration, interval: Duration) -> Self {
+        self.auto_repeat = Some((delay, interval));
+        self
+    }
+
+    /// Process the events from a [`Keypad::poll`] made at time `now`. Call
+    /// this regularly even when there are no events, so that held keys are
+    /// noticed.
+    pub fn update(&mut self, events: Events, now: Duration) -> Actions {
+        for event in events.clone() {
+            match event {
+                KeypadEvent::Pressed(key) => {
+                    self.held = Some(Held {
+                        key,
+                        since: now,
+                        next_repeat: now
+                            + self.auto_repeat.map_or(Duration::ZERO, |(delay, _)| delay),
+                        long_pressed: false,
+                    });
+                }
+                KeypadEvent::Released(key) => {
+                    if self.held.as_ref().is_some_and(|held| held.key == key) {
+                        self.held = None;
+                    }
+                }
+            }
+        }
+
+        let mut extra = [None; 2];
+        if let Some(held) = &mut self.held {
+            let elapsed = now.saturating_sub(held.since);
+
+            if let Some(long_press) = self.long_press {
+                if !held.long_pressed && elapsed >= long_press {
+                    held.long_pressed = true;
+                    extra[0] = Some(KeyAction::LongPress(held.key));
+                }
+            }
+
+            if let Some((_, interval)) = self.auto_repeat {
+                if !elapsed.is_zero() && now >= held.next_repeat {
+                    held.next_repeat += interval;
+                    if held.next_repeat <= now {
+                        // Polled late, don't try to catch up on missed repeats.
+                        held.next_repeat = now + interval;
+                    }
+                    extra[1] = Some(KeyAction::Repeat(held.key));
+                }
+            }
+        }
+
+        Actions { events, extra }
+    }
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The actions from a [`KeyRepeat::update`].
+#[derive(Debug, Clone)]
+pub struct Actions {
+    events: Events,
+    extra: [Option<KeyAction>; 2],
+}
+
+impl Iterator for Actions {
+    type Item = KeyAction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.events
+            .next()
+            .map(KeyAction::from)
+            .or_else(|| self.extra.iter_mut().find_map(Option::take))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::{mock::MockBus, reg::Register, Pins, Sx1509};
+
+    const KEYMAP: Keymap<char, 4, 3> = Keymap::new([
+        ['1', '2', '3'],
+        ['4', '5', '6'],
+        ['7', '8', '9'],
+        ['*', '0', '#'],
+    ]);
+
+    fn key(row: u8, col: u8) -> KeyEvent {
+        KeyEvent { row, col }
+    }
+
+    /// Make the engine report `columns` and `rows` as active.
+    fn hold(bus: &MockBus, columns: u8, rows: u8) {
+        bus.set_register(Register::RegKeyData1 as u8, !columns);
+        bus.set_register(Register::RegKeyData2 as u8, !rows);
+    }
+
+    fn with_keypad(test: impl FnOnce(&MockBus, &mut Keypad<'_, MockBus, 4, 3>)) {
+        let bus = MockBus::new();
+        let mut expander = Sx1509::new(bus.clone(), 0x3E).unwrap();
+        let Pins {
+            a0,
+            a1,
+            a2,
+            a3,
+            b0,
+            b1,
+            b2,
+            ..
+        } = expander.split();
+        let mut keypad = Keypad::new((a0, a1, a2, a3), (b0, b1, b2)).unwrap();
+        test(&bus, &mut keypad);
+    }
+
+    #[test]
+    fn reads_a_single_key() {
+        with_keypad(|bus, keypad| {
+            hold(bus, 1 << 2, 1 << 1);
+            assert_eq!(keypad.read_keys().unwrap().collect::<Vec<_>>(), [key(1, 2)]);
+            assert!(!keypad.ghosted());
+            assert_eq!(keypad.read_mapped(&KEYMAP).unwrap(), Some('6'));
+        });
+    }
+
+    #[test]
+    fn chords_in_one_row_are_not_ghosted() {
+        with_keypad(|bus, keypad| {
+            hold(bus, 1 << 0 | 1 << 2, 1 << 3);
+            assert_eq!(
+                keypad.read_keys().unwrap().collect::<Vec<_>>(),
+                [key(3, 0), key(3, 2)]
+            );
+            assert!(!keypad.ghosted());
+        });
+    }
+
+    #[test]
+    fn ambiguous_keys_are_reported_or_suppressed() {
+        with_keypad(|bus, keypad| {
+            hold(bus, 0b011, 0b0011);
+            assert_eq!(keypad.read_keys().unwrap().len(), 4);
+            assert!(keypad.ghosted());
+
+            keypad.set_ghost_policy(GhostPolicy::Suppress);
+            assert!(keypad.read_keys().unwrap().is_empty());
+            assert!(keypad.ghosted());
+        });
+    }
+
+    #[test]
+    fn unused_rows_and_columns_are_ignored() {
+        with_keypad(|bus, keypad| {
+            // Column 3 and row 4 aren't part of a 4x3 keypad.
+            hold(bus, 1 << 3, 1 << 4);
+            assert!(keypad.read_keys().unwrap().is_empty());
+            assert_eq!(keypad.read_mapped(&KEYMAP).unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn poll_reports_releases_before_presses() {
+        with_keypad(|bus, keypad| {
+            hold(bus, 1 << 0, 1 << 0);
+            assert_eq!(
+                keypad.poll().unwrap().collect::<Vec<_>>(),
+                [KeypadEvent::Pressed(key(0, 0))]
+            );
+
+            hold(bus, 1 << 1, 1 << 0);
+            assert_eq!(
+                keypad.poll().unwrap().collect::<Vec<_>>(),
+                [
+                    KeypadEvent::Released(key(0, 0)),
+                    KeypadEvent::Pressed(key(0, 1))
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn keymap_ignores_keys_outside_it() {
+        assert_eq!(KEYMAP.get(key(3, 2)), Some(&'#'));
+        assert_eq!(KEYMAP.get(key(3, 3)), None);
+        assert_eq!(KEYMAP.get(key(4, 0)), None);
+    }
+}
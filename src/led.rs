@@ -0,0 +1,917 @@
+use core::{marker::PhantomData, time::Duration};
+
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{
+    error::{Error, ModeChange},
+    lock::BusLock,
+    states,
+    time::IntoDuration,
+    Input, Interface, Output,
+};
+
+/// Gamma correction table (gamma 2.2) mapping a perceived brightness to the
+/// LED intensity that produces it. Used by
+/// [`Led::set_brightness_gamma`], but also usable on its own.
+#[rustfmt::skip]
+pub const GAMMA: [u8; 256] = [
+      0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   0,   1,
+      1,   1,   1,   1,   1,   1,   1,   1,   1,   2,   2,   2,   2,   2,   2,   2,
+      3,   3,   3,   3,   3,   4,   4,   4,   4,   5,   5,   5,   5,   6,   6,   6,
+      6,   7,   7,   7,   8,   8,   8,   9,   9,   9,  10,  10,  11,  11,  11,  12,
+     12,  13,  13,  13,  14,  14,  15,  15,  16,  16,  17,  17,  18,  18,  19,  19,
+     20,  20,  21,  22,  22,  23,  23,  24,  25,  25,  26,  26,  27,  28,  28,  29,
+     30,  30,  31,  32,  33,  33,  34,  35,  35,  36,  37,  38,  39,  39,  40,  41,
+     42,  43,  43,  44,  45,  46,  47,  48,  49,  49,  50,  51,  52,  53,  54,  55,
+     56,  57,  58,  59,  60,  61,  62,  63,  64,  65,  66,  67,  68,  69,  70,  71,
+     73,  74,  75,  76,  77,  78,  79,  81,  82,  83,  84,  85,  87,  88,  89,  90,
+     91,  93,  94,  95,  97,  98,  99, 100, 102, 103, 105, 106, 107, 109, 110, 111,
+    113, 114, 116, 117, 119, 120, 121, 123, 124, 126, 127, 129, 130, 132, 133, 135,
+    137, 138, 140, 141, 143, 145, 146, 148, 149, 151, 153, 154, 156, 158, 159, 161,
+    163, 165, 166, 168, 170, 172, 173, 175, 177, 179, 181, 182, 184, 186, 188, 190,
+    192, 194, 196, 197, 199, 201, 203, 205, 207, 209, 211, 213, 215, 217, 219, 221,
+    223, 225, 227, 229, 231, 234, 236, 238, 240, 242, 244, 246, 248, 251, 253, 255,
+];
+
+/// How long [`sync`] holds NRESET low for.
+const SYNC_PULSE_US: u32 = 10;
+
+/// What a low pulse on the NRESET pin does.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NresetFunction {
+    /// Reset the whole chip, equivalent to a power-on reset.
+    #[default]
+    Reset,
+    /// Only restart the LED driver PWM, blink and fade counters, leaving
+    /// every register untouched. This keeps blinking LEDs phase-aligned,
+    /// including across several chips sharing the NRESET line.
+    LedSync,
+}
+
+/// How the LED driver fades between intensities, set per bank.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FadeMode {
+    /// Step the intensity linearly.
+    #[default]
+    Linear,
+    /// Step the intensity logarithmically, which looks more even to the eye.
+    Logarithmic,
+}
+
+/// Restart the LED timers of every SX1509 whose NRESET pin is connected to
+/// `nreset`, by pulsing it low. Every chip on the line must have been
+/// configured with [`NresetFunction::LedSync`], otherwise this resets it.
+///
+/// # Errors
+/// This function will return an error if driving `nreset` fails.
+pub fn sync<P, D>(nreset: &mut P, delay: &mut D) -> Result<(), P::Error>
+where
+    P: OutputPin,
+    D: DelayNs,
+{
+    nreset.set_low()?;
+    delay.delay_us(SYNC_PULSE_US);
+    nreset.set_high()
+}
+
+/// Converts between durations and LED driver timing register values, for a
+/// given LED driver clock. Get the one for the current clock from
+/// [`Sx1509::led_timing`](crate::Sx1509::led_timing).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedTiming {
+    clock_hz: u32,
+}
+
+/// A timing register value, together with the duration it actually results
+/// in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimingValue {
+    /// The value to write to the timing register.
+    pub register: u8,
+    /// The duration that `register` results in.
+    pub actual: Duration,
+}
+
+impl LedTiming {
+    /// Timing for an LED driver clock of `clock_hz`.
+    ///
+    /// # Panics
+    /// This function panics if `clock_hz` is zero.
+    #[must_use]
+    pub const fn new(clock_hz: u32) -> Self {
+        assert!(clock_hz != 0, "the LED driver clock must be running");
+        Self { clock_hz }
+    }
+
+    /// The LED driver clock frequency this timing was created for.
+    #[must_use]
+    pub const fn clock_hz(self) -> u32 {
+        self.clock_hz
+    }
+
+    /// The on time register value closest to `target`. A register value of
+    /// `0` (static mode) is never returned.
+    #[must_use]
+    pub fn on_time(self, target: impl IntoDuration) -> TimingValue {
+        self.nearest(target.into_duration(), 1, |register| {
+            if register < 16 {
+                64 * u64::from(register)
+            } else {
+                512 * u64::from(register)
+            }
+        })
+    }
+
+    /// The off time register value closest to `target`. The off time follows
+    /// the same formula as [`on_time`](Self::on_time).
+    #[must_use]
+    pub fn off_time(self, target: impl IntoDuration) -> TimingValue {
+        self.on_time(target)
+    }
+
+    /// The fade in register value closest to `target`. The fade time depends
+    /// on how far the intensity has to travel, so it needs the on intensity
+    /// and the (3 bit) off intensity register value. A target of zero
+    /// disables fading.
+    #[must_use]
+    pub fn rise_time(
+        self,
+        target: impl IntoDuration,
+        on_intensity: u8,
+        off_intensity: u8,
+    ) -> TimingValue {
+        let steps = u64::from(on_intensity.saturating_sub(4 * (off_intensity & 0b111)));
+        self.nearest(target.into_duration(), 0, |register| {
+            if register < 16 {
+                steps * u64::from(register)
+            } else {
+                16 * steps * u64::from(register)
+            }
+        })
+    }
+
+    /// The fade out register value closest to `target`. The fade out time
+    /// follows the same formula as [`rise_time`](Self::rise_time).
+    #[must_use]
+    pub fn fall_time(
+        self,
+        target: impl IntoDuration,
+        on_intensity: u8,
+        off_intensity: u8,
+    ) -> TimingValue {
+        self.rise_time(target, on_intensity, off_intensity)
+    }
+
+    /// Search the register values from `min` to 31 for the one closest to
+    /// `target`, where `periods` gives the duration of a register value in
+    /// units of 255 `ClkX` periods.
+    fn nearest(self, target: Duration, min: u8, periods: impl Fn(u8) -> u64) -> TimingValue {
+        let target = target.as_nanos();
+        (min..=31)
+            .map(|register| TimingValue {
+                register,
+                actual: Duration::from_nanos(
+                    periods(register) * 255 * 1_000_000_000 / u64::from(self.clock_hz),
+                ),
+            })
+            .min_by_key(|value| value.actual.as_nanos().abs_diff(target))
+            .unwrap_or(TimingValue {
+                register: min,
+                actual: Duration::ZERO,
+            })
+    }
+}
+
+/// The LED driver register values of a pin. Build one from durations with
+/// [`PatternBuilder`], and apply it with [`Led::configure`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LedConfig {
+    /// The intensity while on, `RegIOn`.
+    pub on_intensity: u8,
+    /// The on time register value, `RegTOn`. `0` disables blinking.
+    pub on_time: u8,
+    /// The off time register value, the top 5 bits of `RegOff`. `0` disables
+    /// blinking.
+    pub off_time: u8,
+    /// The intensity while off, in steps of 4, the bottom 3 bits of `RegOff`.
+    pub off_intensity: u8,
+    /// The fade in register value, `RegTRise`. `0` disables fading in.
+    pub rise_time: u8,
+    /// The fade out register value, `RegTFall`. `0` disables fading out.
+    pub fall_time: u8,
+}
+
+/// Builds an [`LedConfig`] from durations, with presets for common status
+/// LED patterns.
+///
+/// The chip only supports a single repeating on/off period, so patterns like
+/// a double blink can't be expressed here.
+///
+/// ```rust
+/// # use sx1509_eh::{led::PatternBuilder, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let timing = expander.led_timing()?.unwrap();
+/// # let Pins { a0, .. } = expander.split();
+/// # let mut led = a0.into_led()?;
+/// led.configure(PatternBuilder::slow_breathe().build(timing))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternBuilder {
+    on_intensity: u8,
+    off_intensity: u8,
+    on_time: Duration,
+    off_time: Duration,
+    rise_time: Duration,
+    fall_time: Duration,
+}
+
+impl PatternBuilder {
+    /// A pattern that is fully on, without blinking or fading.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            on_intensity: u8::MAX,
+            off_intensity: 0,
+            on_time: Duration::ZERO,
+            off_time: Duration::ZERO,
+            rise_time: Duration::ZERO,
+            fall_time: Duration::ZERO,
+        }
+    }
+
+    /// A slow, calm breathing pattern with a period of about three seconds.
+    #[must_use]
+    pub const fn slow_breathe() -> Self {
+        Self::new()
+            .on_time(Duration::from_millis(500))
+            .off_time(Duration::from_millis(500))
+            .rise_time(Duration::from_secs(1))
+            .fall_time(Duration::from_secs(1))
+    }
+
+    /// A short flash that fades out, once a second.
+    #[must_use]
+    pub const fn fast_pulse() -> Self {
+        Self::new()
+            .on_time(Duration::from_millis(50))
+            .off_time(Duration::from_millis(700))
+            .rise_time(Duration::from_millis(30))
+            .fall_time(Duration::from_millis(250))
+    }
+
+    /// Blink on and off once a second.
+    #[must_use]
+    pub const fn slow_blink() -> Self {
+        Self::new()
+            .on_time(Duration::from_millis(500))
+            .off_time(Duration::from_millis(500))
+    }
+
+    /// Blink on and off five times a second.
+    #[must_use]
+    pub const fn fast_blink() -> Self {
+        Self::new()
+            .on_time(Duration::from_millis(100))
+            .off_time(Duration::from_millis(100))
+    }
+
+    /// Set the intensity while on.
+    #[must_use]
+    pub const fn on_intensity(mut self, intensity: u8) -> Self {
+        self.on_intensity = intensity;
+        self
+    }
+
+    /// Set the (3 bit) intensity while off, in steps of 4.
+    #[must_use]
+    pub const fn off_intensity(mut self, intensity: u8) -> Self {
+        self.off_intensity = intensity;
+        self
+    }
+
+    /// Set how long the LED stays on. Zero disables blinking.
+    #[must_use]
+    pub const fn on_time(mut self, time: Duration) -> Self {
+        self.on_time = time;
+        self
+    }
+
+    /// Set how long the LED stays off. Zero disables blinking.
+    #[must_use]
+    pub const fn off_time(mut self, time: Duration) -> Self {
+        self.off_time = time;
+        self
+    }
+
+    /// Set how long the LED takes to fade in. Zero disables fading in.
+    #[must_use]
+    pub const fn rise_time(mut self, time: Duration) -> Self {
+        self.rise_time = time;
+        self
+    }
+
+    /// Set how long the LED takes to fade out. Zero disables fading out.
+    #[must_use]
+    pub const fn fall_time(mut self, time: Duration) -> Self {
+        self.fall_time = time;
+        self
+    }
+
+    /// Convert the durations into the nearest register values for `timing`.
+    #[must_use]
+    pub fn build(&self, timing: LedTiming) -> LedConfig {
+        let blinking = !self.on_time.is_zero() && !self.off_time.is_zero();
+        let time = |time: Duration| {
+            if blinking {
+                timing.on_time(time).register
+            } else {
+                0
+            }
+        };
+        let fade = |time: Duration| {
+            if time.is_zero() {
+                0
+            } else {
+                timing
+                    .rise_time(time, self.on_intensity, self.off_intensity)
+                    .register
+            }
+        };
+
+        LedConfig {
+            on_intensity: self.on_intensity,
+            on_time: time(self.on_time),
+            off_time: time(self.off_time),
+            off_intensity: self.off_intensity & 0b111,
+            rise_time: fade(self.rise_time),
+            fall_time: fade(self.fall_time),
+        }
+    }
+}
+
+impl Default for PatternBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A pin on the SX1509 configured to use the LED driver. The LED should be
+/// connected between the pin and the supply, as the pin is driven open-drain.
+pub struct Led<'a, const PIN: u8, I2C: BusLock> {
+    pub(crate) interface: &'a Interface<I2C>,
+}
+
+impl<'a, const PIN: u8, I2C, E> Led<'a, PIN, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Stop using the LED driver and configure the pin as a push-pull output.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the pin can be extracted from the
+    /// [`ModeChange`], although it may be partially configured.
+    pub fn into_output(
+        self,
+    ) -> Result<Output<'a, PIN, I2C, states::PushPull>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.disable_led_driver::<PIN>()?;
+            stage.set_open_drain::<PIN>(false)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Output {
+                interface: self.interface,
+                _state: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Stop using the LED driver and configure the pin as an input.
+    ///
+    /// # Errors
+    /// See [`into_output`](Self::into_output).
+    pub fn into_input(
+        self,
+    ) -> Result<
+        Input<'a, PIN, I2C, states::Floating, states::DebounceOff, states::InterruptOff>,
+        ModeChange<Error<E>, Self>,
+    > {
+        let result = self.interface.coalesce(|stage| {
+            stage.disable_led_driver::<PIN>()?;
+            stage.set_open_drain::<PIN>(false)?;
+            stage.set_input::<PIN>()?;
+            stage.set_pull_down::<PIN>(false)?;
+            stage.set_debounce_enable::<PIN>(false)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Input {
+                interface: self.interface,
+                _state: PhantomData,
+                _debounce: PhantomData,
+                _interrupt: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Set the raw "on" intensity of the LED, where `0` is off and `255` is
+    /// fully on.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    pub fn set_intensity(&mut self, intensity: u8) -> Result<(), Error<E>> {
+        self.interface.set_led_intensity::<PIN>(intensity)
+    }
+
+    /// Set the brightness of the LED, corrected using [`GAMMA`] so that equal
+    /// steps in `brightness` look like equal steps in brightness to the eye.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    pub fn set_brightness_gamma(&mut self, brightness: u8) -> Result<(), Error<E>> {
+        self.set_intensity(GAMMA[usize::from(brightness)])
+    }
+
+    /// Get a [`LedChannel`] for this LED, to drive it from a [`Sequencer`].
+    #[must_use]
+    pub fn channel(&self) -> LedChannel<'a, I2C> {
+        LedChannel {
+            interface: self.interface,
+            pin: PIN,
+        }
+    }
+
+    /// Make the LED blink, using register values from [`LedTiming`]. An
+    /// `on_time` or `off_time` of `0` disables blinking. During the off
+    /// period the LED is driven at the (3 bit) `off_intensity`, in steps of
+    /// 4.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    pub fn set_blink(
+        &mut self,
+        on_time: u8,
+        off_time: u8,
+        off_intensity: u8,
+    ) -> Result<(), Error<E>> {
+        self.interface
+            .set_led_blink::<PIN>(on_time, off_time, off_intensity)
+    }
+
+    /// Set the duty cycle of the LED as a percentage. Values above 100 are
+    /// treated as 100.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    pub fn set_duty_percent(&mut self, percent: u8) -> Result<(), Error<E>> {
+        self.set_duty_fraction(u16::from(percent), 100)
+    }
+
+    /// Set the duty cycle of the LED as the fraction `numerator / denominator`,
+    /// rounded to the nearest intensity. Fractions above one (or with a
+    /// denominator of zero) are treated as one.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    pub fn set_duty_fraction(&mut self, numerator: u16, denominator: u16) -> Result<(), Error<E>> {
+        let intensity = if numerator >= denominator {
+            u8::MAX
+        } else {
+            let (numerator, denominator) = (u32::from(numerator), u32::from(denominator));
+            let intensity = (numerator * 255 + denominator / 2) / denominator;
+            u8::try_from(intensity).unwrap_or(u8::MAX)
+        };
+
+        self.set_intensity(intensity)
+    }
+
+    /// Write every LED driver register of the pin at once. On pins without
+    /// fade support (0-3 and 8-11) the fade times are ignored.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    pub fn configure(&mut self, config: LedConfig) -> Result<(), Error<E>> {
+        self.interface.configure_led::<PIN>(config)
+    }
+
+    /// Read back every LED driver register of the pin. Useful for debugging,
+    /// or for restoring the LED after reconfiguring it. On pins without fade
+    /// support (0-3 and 8-11) the fade times are reported as `0`.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn config(&mut self) -> Result<LedConfig, Error<E>> {
+        self.interface.led_config::<PIN>()
+    }
+
+    /// Make the LED fade in and out when blinking, using register values from
+    /// [`LedTiming`]. A value of `0` disables the fade. Only pins 4-7 and
+    /// 12-15 can fade, using this on any other pin fails to compile.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    pub fn set_fade(&mut self, rise: u8, fall: u8) -> Result<(), Error<E>> {
+        self.interface.set_led_fade::<PIN>(rise, fall)
+    }
+}
+
+/// A set of LED intensities to be written together. Staging the channels of
+/// an RGB LED and flushing them at once avoids the visible color tearing of
+/// updating them one by one.
+///
+/// ```rust
+/// # use sx1509_eh::{led::IntensityBatch, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { a0, a1, a2, .. } = expander.split();
+/// # let (red, green, blue) = (a0.into_led()?, a1.into_led()?, a2.into_led()?);
+/// let mut batch = IntensityBatch::new();
+/// batch.stage(&red, 255).stage(&green, 64).stage(&blue, 0);
+/// batch.flush()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct IntensityBatch<'a, I2C: BusLock> {
+    interface: Option<&'a Interface<I2C>>,
+    intensities: [Option<u8>; 16],
+}
+
+impl<'a, I2C, E> IntensityBatch<'a, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Create an empty batch.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            interface: None,
+            intensities: [None; 16],
+        }
+    }
+
+    /// Stage an intensity for `led`, replacing any value already staged for
+    /// it.
+    ///
+    /// # Panics
+    /// All LEDs in a batch must belong to the same expander. This function
+    /// panics if `led` belongs to a different expander than the LEDs already
+    /// staged.
+    pub fn stage<const PIN: u8>(&mut self, led: &Led<'a, PIN, I2C>, intensity: u8) -> &mut Self {
+        let interface = *self.interface.get_or_insert(led.interface);
+        assert!(
+            core::ptr::eq(interface, led.interface),
+            "LEDs in a batch must belong to the same expander"
+        );
+
+        self.intensities[usize::from(PIN)] = Some(intensity);
+        self
+    }
+
+    /// Write all staged intensities and clear the batch. The writes are
+    /// grouped into a single burst per bank.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    /// The batch is left untouched if an error occurs, so it can be retried.
+    pub fn flush(&mut self) -> Result<(), Error<E>> {
+        if let Some(interface) = self.interface {
+            interface.set_led_intensities(&self.intensities)?;
+        }
+
+        self.intensities = [None; 16];
+        Ok(())
+    }
+}
+
+impl<I2C, E> Default for IntensityBatch<'_, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A handle to the intensity of one LED, without the pin number in its type,
+/// so that LEDs on different pins can be grouped together. Get one from
+/// [`Led::channel`].
+///
+/// A channel only ever writes the LED's intensity register, which has no
+/// effect once the pin is no longer driven by the LED driver.
+pub struct LedChannel<'a, I2C: BusLock> {
+    interface: &'a Interface<I2C>,
+    pin: u8,
+}
+
+/// One step of a [`Sequencer`] pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step<const N: usize> {
+    /// The intensity of each channel during this step.
+    pub intensities: [u8; N],
+    /// How many calls to [`Sequencer::tick`] this step lasts. A value of `0`
+    /// is treated as `1`.
+    pub ticks: u16,
+}
+
+/// Steps through a pattern of intensities across a set of LEDs, for effects
+/// like chase patterns. Call [`tick`](Self::tick) at a steady rate, for
+/// example from a timer interrupt or an async ticker loop.
+///
+/// Only intensities that change between steps are written, and these are
+/// written in a single burst per bank.
+///
+/// ```rust
+/// # use sx1509_eh::{led::{Sequencer, Step}, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, delay: &mut impl embedded_hal::delay::DelayNs) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { a0, a1, a2, .. } = expander.split();
+/// # let (a, b, c) = (a0.into_led()?, a1.into_led()?, a2.into_led()?);
+/// const CHASE: [Step<3>; 3] = [
+///     Step { intensities: [255, 0, 0], ticks: 5 },
+///     Step { intensities: [0, 255, 0], ticks: 5 },
+///     Step { intensities: [0, 0, 255], ticks: 5 },
+/// ];
+///
+/// let mut sequencer = Sequencer::new([a.channel(), b.channel(), c.channel()], &CHASE);
+/// loop {
+///     sequencer.tick()?;
+///     delay.delay_ms(20);
+/// }
+/// # }
+/// ```
+pub struct Sequencer<'a, 's, I2C: BusLock, const N: usize> {
+    interface: &'a Interface<I2C>,
+    pins: [u8; N],
+    steps: &'s [Step<N>],
+    next: usize,
+    remaining: u16,
+    current: Option<[u8; N]>,
+}
+
+impl<'a, 's, I2C, E, const N: usize> Sequencer<'a, 's, I2C, N>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Create a sequencer that drives `channels` through `steps`, repeating
+    /// once the last step is done.
+    ///
+    /// # Panics
+    /// This function panics if `channels` is empty, or if the channels belong
+    /// to different expanders.
+    #[must_use]
+    pub fn new(channels: [LedChannel<'a, I2C>; N], steps: &'s [Step<N>]) -> Self {
+        Self {
+            interface: shared_interface(&channels),
+            pins: channels.map(|channel| channel.pin),
+            steps,
+            next: 0,
+            remaining: 0,
+            current: None,
+        }
+    }
+
+    /// Advance the pattern by one tick, writing the next step's intensities
+    /// if the current step is done.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`LedClockOff`](Error::LedClockOff) if the LED driver clock isn't
+    /// running.
+    /// The step is retried on the next tick if an error occurs.
+    pub fn tick(&mut self) -> Result<(), Error<E>> {
+        if self.remaining == 0 {
+            let Some(step) = self.steps.get(self.next) else {
+                return Ok(());
+            };
+
+            let mut intensities = [None; 16];
+            for (i, (&pin, &intensity)) in self.pins.iter().zip(&step.intensities).enumerate() {
+                if self.current.is_none_or(|current| current[i] != intensity) {
+                    intensities[usize::from(pin)] = Some(intensity);
+                }
+            }
+            self.interface.set_led_intensities(&intensities)?;
+
+            self.current = Some(step.intensities);
+            self.remaining = step.ticks.max(1);
+            self.next = (self.next + 1) % self.steps.len();
+        }
+
+        self.remaining -= 1;
+        Ok(())
+    }
+
+    /// Restart the pattern from the first step on the next tick.
+    pub fn restart(&mut self) {
+        self.next = 0;
+        self.remaining = 0;
+    }
+}
+
+/// Starts blinking or fading a group of LEDs with a phase offset between them,
+/// for wave effects across a row of LEDs.
+///
+/// The LED driver starts a pin's blink cycle when its data bit is cleared, so
+/// the offsets are produced by stopping every LED and then starting them one
+/// by one in order of their offsets. A later [`sync`] restarts every LED at
+/// once, and so undoes the offsets.
+///
+/// ```rust
+/// # use sx1509_eh::{led::FadeGroup, Pins, Sx1509};
+/// # use core::time::Duration;
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, mut delay: impl embedded_hal::delay::DelayNs) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// # let Pins { a0, a1, a2, a3, .. } = expander.split();
+/// # let (a, b, c) = (a0.into_led()?, a1.into_led()?, a2.into_led()?);
+/// # let d = a3.into_led()?;
+/// let mut wave = FadeGroup::wave(
+///     [a.channel(), b.channel(), c.channel(), d.channel()],
+///     Duration::from_millis(150),
+/// );
+/// wave.start(&mut delay)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FadeGroup<'a, I2C: BusLock, const N: usize> {
+    interface: &'a Interface<I2C>,
+    pins: [u8; N],
+    offsets: [Duration; N],
+}
+
+impl<'a, I2C, E, const N: usize> FadeGroup<'a, I2C, N>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Create a group that starts each of `channels` after the matching
+    /// offset.
+    ///
+    /// # Panics
+    /// This function panics if `channels` is empty, or if the channels belong
+    /// to different expanders.
+    #[must_use]
+    pub fn new(channels: [LedChannel<'a, I2C>; N], offsets: [Duration; N]) -> Self {
+        Self {
+            interface: shared_interface(&channels),
+            pins: channels.map(|channel| channel.pin),
+            offsets,
+        }
+    }
+
+    /// Create a group that starts `channels` in order, `step` apart.
+    ///
+    /// # Panics
+    /// See [`new`](Self::new).
+    #[must_use]
+    pub fn wave(channels: [LedChannel<'a, I2C>; N], step: Duration) -> Self {
+        let mut offset = Duration::ZERO;
+        let offsets = [(); N].map(|()| {
+            let this = offset;
+            offset += step;
+            this
+        });
+
+        Self::new(channels, offsets)
+    }
+
+    /// Stop every LED in the group, then start each one after its offset. This
+    /// blocks for the longest offset.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn start<D: DelayNs>(&mut self, delay: &mut D) -> Result<(), Error<E>> {
+        self.stop()?;
+
+        let mut order = core::array::from_fn::<usize, N, _>(|i| i);
+        order.sort_unstable_by_key(|&i| self.offsets[i]);
+
+        let mut elapsed = Duration::ZERO;
+        for i in order {
+            let wait = self.offsets[i].saturating_sub(elapsed);
+            delay.delay_us(u32::try_from(wait.as_micros()).unwrap_or(u32::MAX));
+            elapsed = self.offsets[i];
+
+            self.interface.set_data_mask(1 << self.pins[i], false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Stop every LED in the group.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn stop(&mut self) -> Result<(), Error<E>> {
+        let mask = self.pins.iter().fold(0, |mask, &pin| mask | 1 << pin);
+        self.interface.set_data_mask(mask, true)
+    }
+}
+
+/// The expander shared by every channel.
+///
+/// # Panics
+/// This function panics if `channels` is empty, or if the channels belong to
+/// different expanders.
+fn shared_interface<'a, I2C: BusLock>(channels: &[LedChannel<'a, I2C>]) -> &'a Interface<I2C> {
+    let Some(first) = channels.first() else {
+        panic!("at least one LED is required");
+    };
+    assert!(
+        channels
+            .iter()
+            .all(|channel| core::ptr::eq(channel.interface, first.interface)),
+        "every LED must belong to the same expander"
+    );
+
+    first.interface
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A clock where 255 periods take exactly 1ms.
+    const TIMING: LedTiming = LedTiming::new(255_000);
+
+    #[test]
+    fn on_time_picks_the_closest_register() {
+        let value = TIMING.on_time(Duration::from_millis(130));
+        assert_eq!(value.register, 2);
+        assert_eq!(value.actual, Duration::from_millis(128));
+
+        // Above 15, each step is 512ms rather than 64ms.
+        let value = TIMING.on_time(Duration::from_secs(9));
+        assert_eq!(value.register, 18);
+        assert_eq!(value.actual, Duration::from_millis(9216));
+    }
+
+    #[test]
+    fn on_time_never_picks_static_mode() {
+        assert_eq!(TIMING.on_time(Duration::ZERO).register, 1);
+    }
+
+    #[test]
+    fn on_time_saturates() {
+        let value = TIMING.on_time(Duration::from_secs(30));
+        assert_eq!(value.register, 31);
+        assert_eq!(value.actual, Duration::from_millis(512 * 31));
+    }
+
+    #[test]
+    fn rise_time_depends_on_the_intensity_range() {
+        // 255 steps of intensity take 255ms per register step.
+        let value = TIMING.rise_time(Duration::from_millis(500), 255, 0);
+        assert_eq!(value.register, 2);
+        assert_eq!(value.actual, Duration::from_millis(510));
+
+        // An off intensity of 4 * 7 leaves 227 steps.
+        let value = TIMING.rise_time(Duration::from_millis(500), 255, 7);
+        assert_eq!(value.actual, Duration::from_millis(454));
+    }
+
+    #[test]
+    fn rise_time_of_zero_disables_fading() {
+        let value = TIMING.rise_time(Duration::ZERO, 255, 0);
+        assert_eq!(value.register, 0);
+        assert_eq!(value.actual, Duration::ZERO);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{
+    error::{Error, ModeChange},
+    lock::BusLock,
+    Interface, Pin,
+};
+
+/// The direction a [`LevelShifter`] translates in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// The bank A pin drives the bank B pin, shifting from VCCA to VCCB.
+    AToB = 0b01,
+    /// The bank B pin drives the bank A pin, shifting from VCCB to VCCA.
+    BToA = 0b10,
+}
+
+/// A pair of pins acting as a level shifter between the supplies of the two
+/// banks, such as between a 1.8V and a 3.3V part. Only the pins with the same
+/// number in both banks pair up, so `a3` with `b3`.
+///
+/// ```rust
+/// # use sx1509_eh::{level_shifter::{Direction, LevelShifter}, Pins, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let Pins { a0, b0, .. } = expander.split();
+/// let shifter = LevelShifter::new(a0, b0, Direction::AToB)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct LevelShifter<'a, const A: u8, const B: u8, I2C: BusLock> {
+    interface: &'a Interface<I2C>,
+    a: Pin<'a, A, I2C>,
+    b: Pin<'a, B, I2C>,
+}
+
+impl<'a, const A: u8, const B: u8, I2C, E> LevelShifter<'a, A, B, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Pair up `a` and `b` as a level shifter translating in `direction`.
+    /// Using pins that don't pair up fails to compile.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the pins can be extracted from the [`ModeChange`].
+    pub fn new(
+        a: Pin<'a, A, I2C>,
+        b: Pin<'a, B, I2C>,
+        direction: Direction,
+    ) -> Result<Self, ModeChange<Error<E>, (Pin<'a, A, I2C>, Pin<'a, B, I2C>)>> {
+        const {
+            assert!(
+                A < 8 && B == A + 8,
+                "level shifters pair a bank A pin with the same bank B pin"
+            );
+        }
+
+        let interface = a.interface;
+        match interface.set_level_shifter(A, Some(direction)) {
+            Ok(()) => Ok(Self { interface, a, b }),
+            Err(error) => Err(ModeChange { error, pin: (a, b) }),
+        }
+    }
+
+    /// Change the direction the pair translates in.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_direction(&mut self, direction: Direction) -> Result<(), Error<E>> {
+        self.interface.set_level_shifter(A, Some(direction))
+    }
+
+    /// Turn the level shifter off and return the pins.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the level shifter can be extracted from the
+    /// [`ModeChange`].
+    pub fn release(self) -> Result<(Pin<'a, A, I2C>, Pin<'a, B, I2C>), ModeChange<Error<E>, Self>> {
+        match self.interface.set_level_shifter(A, None) {
+            Ok(()) => Ok((self.a, self.b)),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+}
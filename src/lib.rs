@@ -1,46 +1,332 @@
 #![doc = include_str!("../README.md")]
 #![no_std]
 
-pub use interface::DebounceTime;
-use interface::Interface;
-pub use pin::{Input, Output, Pin};
+use core::time::Duration;
+
+use embedded_hal::{delay::DelayNs, digital::OutputPin, i2c::I2c};
+
+use interface::{Bank, Interface};
+pub use interface::{
+    DebounceTime, LowPowerState, MiscConfig, PinConfig, RetryPolicy, Stats, REGISTER_COUNT,
+};
+use led::NresetFunction;
+use lock::BusLock;
+pub use pin::{Disconnected, Input, Output, Pin};
 
 mod interface;
+#[cfg(test)]
+mod mock;
 mod pin;
+#[cfg(not(feature = "raw"))]
 mod reg;
 
+/// Button support.
+pub mod button;
+/// Clock configuration.
+pub mod clock;
+/// Drive profiles for tuning the electrical characteristics of pins.
+pub mod drive;
+/// Rotary encoder support.
+pub mod encoder;
 /// Error types.
 pub mod error;
+/// Groups of output pins written together.
+pub mod group;
+/// Interrupt support.
+pub mod interrupt;
+/// Keypad scanning engine support.
+pub mod keypad;
+/// LED driver support.
+pub mod led;
+/// Level shifter support.
+pub mod level_shifter;
+/// Locks guarding the shared I2C bus.
+pub mod lock;
+/// The register map, for raw register access.
+#[cfg(feature = "raw")]
+pub mod reg;
+/// Chip-wide settings while the pins are split.
+pub mod settings;
 /// State types for the pins.
 pub mod states;
+/// Durations for the timing APIs.
+pub mod time;
+
+/// The I2C address of the SX1509, as strapped by its ADDR1 and ADDR0 pins.
+/// The constructors also take a raw `u8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Address {
+    /// ADDR1 low, ADDR0 low: `0x3E`. This is the SparkFun breakout's default.
+    Addr00 = 0x3E,
+    /// ADDR1 low, ADDR0 high: `0x3F`.
+    Addr01 = 0x3F,
+    /// ADDR1 high, ADDR0 low: `0x70`.
+    Addr10 = 0x70,
+    /// ADDR1 high, ADDR0 high: `0x71`.
+    Addr11 = 0x71,
+}
+
+impl From<Address> for u8 {
+    fn from(address: Address) -> Self {
+        address as u8
+    }
+}
+
+/// How long to give the device after a reset before writing to it.
+const RESET_SETTLE_US: u32 = 1000;
+
+/// How long to hold NRESET low for a hardware reset.
+const RESET_PULSE_US: u32 = 10;
+
+/// The default address of the SparkFun SX1509 breakout, with both ADDR
+/// jumpers open.
+pub const SPARKFUN_ADDRESS: u8 = Address::Addr00 as u8;
 
 /// The SX1509 driver. Use [`new`](Self::new) to create a new instance of the
 /// driver, and then [`split`](Self::split) to get individual pins that support
 /// the [`embedded_hal`] traits.
-pub struct Sx1509<I2C> {
+pub struct Sx1509<I2C: BusLock> {
     interface: Interface<I2C>,
 }
 
 impl<I2C, E> Sx1509<I2C>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Create a new instance of the SX1509 driver. This performs a reset of the
-    /// device and may fail if the device is not present.
+    /// device and may fail if the device is not present. The internal 2MHz
+    /// oscillator is started, see [`new_with_clock`](Self::new_with_clock)
+    /// to clock the chip differently.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails for
+    /// any reason.
+    pub fn new(i2c: I2C, address: impl Into<u8>) -> Result<Self, E> {
+        Self::new_with_clock(i2c, address, clock::ClockConfig::new())
+    }
+
+    /// Create a new instance of the SX1509 driver, clocked according to
+    /// `clock`. This performs a reset of the device and may fail if the device
+    /// is not present.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails for
+    /// any reason.
+    pub fn new_with_clock(
+        i2c: I2C,
+        address: impl Into<u8>,
+        clock: clock::ClockConfig,
+    ) -> Result<Self, E> {
+        let address = address.into();
+        let mut i2c = i2c.into_mutex();
+        Self::soft_reset(I2C::get_mut(&mut i2c), address)?;
+        Self::from_reset(i2c, address, clock)
+    }
+
+    /// Create a new instance of the SX1509 driver, clocked according to
+    /// `clock`, waiting with `delay` for the device to settle after its reset
+    /// before configuring it. Fast MCUs can otherwise write to the device
+    /// before it is ready, which fails intermittently.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails for
+    /// any reason.
+    pub fn new_with_delay<D: DelayNs>(
+        i2c: I2C,
+        address: impl Into<u8>,
+        clock: clock::ClockConfig,
+        delay: &mut D,
+    ) -> Result<Self, E> {
+        let address = address.into();
+        let mut i2c = i2c.into_mutex();
+        Self::soft_reset(I2C::get_mut(&mut i2c), address)?;
+        delay.delay_us(RESET_SETTLE_US);
+        Self::from_reset(i2c, address, clock)
+    }
+
+    /// Create a new instance of the SX1509 driver, clocked according to
+    /// `clock`, resetting the device by pulsing its NRESET pin low through
+    /// `nreset`. A software reset follows, in case an earlier configuration
+    /// made NRESET [sync the LEDs](NresetFunction::LedSync) instead. `delay`
+    /// times the pulse and lets the device settle after each reset.
+    ///
+    /// `nreset` is only borrowed, so it can be kept for [`led::sync`].
+    ///
+    /// # Errors
+    /// This function will return an error if driving `nreset` or
+    /// communication with I2C fails.
+    pub fn new_with_nreset<P, D>(
+        i2c: I2C,
+        address: impl Into<u8>,
+        clock: clock::ClockConfig,
+        nreset: &mut P,
+        delay: &mut D,
+    ) -> Result<Self, error::NresetError<E, P::Error>>
+    where
+        P: OutputPin,
+        D: DelayNs,
+    {
+        let address = address.into();
+        nreset.set_low().map_err(error::NresetError::Pin)?;
+        delay.delay_us(RESET_PULSE_US);
+        nreset.set_high().map_err(error::NresetError::Pin)?;
+        delay.delay_us(RESET_SETTLE_US);
+
+        let mut i2c = i2c.into_mutex();
+        Self::soft_reset(I2C::get_mut(&mut i2c), address).map_err(error::NresetError::I2c)?;
+        delay.delay_us(RESET_SETTLE_US);
+        Self::from_reset(i2c, address, clock).map_err(error::NresetError::I2c)
+    }
+}
+
+impl<I2C> Sx1509<I2C>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    /// Check for a responsive device at `address` with a harmless read of
+    /// `RegInterruptMaskB`, before committing to a reset with one of the
+    /// constructors. The bus is only borrowed, so it can be passed on
+    /// afterwards, or wrapped in a lock first.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::Sx1509;
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(mut i2c: I2C) -> Result<(), Failed> {
+    /// Sx1509::probe(&mut i2c, 0x3E)?;
+    /// let expander = Sx1509::new(i2c, 0x3E)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return [`NotFound`](error::Error::NotFound) if
+    /// nothing acknowledges the address, or an error if communication with
+    /// I2C fails otherwise.
+    pub fn probe(i2c: &mut I2C, address: impl Into<u8>) -> Result<(), error::Error<I2C::Error>> {
+        use embedded_hal::i2c::{Error, ErrorKind};
+
+        let mut data = [0];
+        i2c.write_read(
+            address.into(),
+            &[reg::Register::RegInterruptMaskB as u8],
+            &mut data,
+        )
+        .map_err(|error| match error.kind() {
+            ErrorKind::NoAcknowledge(_) => error::Error::NotFound,
+            _ => error::Error::Io(error),
+        })
+    }
+}
+
+impl<I2C, E> Sx1509<I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Wrap a device that is already configured, such as by a bootloader or an
+    /// earlier firmware stage, without resetting it or touching its clock.
+    /// The clock setup is read back so timing conversions stay correct.
+    ///
+    /// The chip can't tell the frequency of an
+    /// [external clock](clock::ClockSource::External), so the driver treats it
+    /// as off until it is given with
+    /// [`set_clock_source`](Self::set_clock_source).
     ///
     /// # Errors
     /// This function will return an error if communication with I2C fails for
     /// any reason.
-    pub fn new(mut i2c: I2C, address: u8) -> Result<Self, E> {
-        // Reset the device.
+    pub fn adopt(i2c: I2C, address: impl Into<u8>) -> Result<Self, E> {
+        let address = address.into();
+        let mut i2c = i2c.into_mutex();
+        let mut data = [0; 2];
+        I2C::get_mut(&mut i2c).write_read(address, &[reg::Register::RegClock as u8], &mut data)?;
+        let clock = clock::ClockConfig::from_bits(data[0], data[1], 0);
+
+        Ok(Self {
+            interface: Interface::new(i2c, address, &clock),
+        })
+    }
+
+    /// Create a new instance of the SX1509 driver for the SparkFun SX1509
+    /// breakout, at its default address of [`SPARKFUN_ADDRESS`] with the ADDR
+    /// jumpers open. The internal oscillator and the LED driver clock are
+    /// started, so debouncing, the keypad engine and LEDs work straight away.
+    ///
+    /// The breakout pulls NRESET up, so it can be left unconnected. If it is
+    /// wired to the MCU, use
+    /// [`new_sparkfun_with_nreset`](Self::new_sparkfun_with_nreset) instead.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::{Pins, Sx1509};
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// let mut expander = Sx1509::new_sparkfun(i2c)?;
+    /// let Pins { a0, .. } = expander.split();
+    /// let mut led = a0.into_led()?;
+    /// led.set_blink(10, 10, 0)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails for
+    /// any reason.
+    pub fn new_sparkfun(i2c: I2C) -> Result<Self, E> {
+        Self::new_with_clock(
+            i2c,
+            SPARKFUN_ADDRESS,
+            clock::ClockConfig::new().led_divider(1),
+        )
+    }
+
+    /// Create a new instance of the SX1509 driver for the SparkFun SX1509
+    /// breakout, like [`new_sparkfun`](Self::new_sparkfun), with its RST pin
+    /// wired to `nreset` for a [hardware reset](Self::new_with_nreset).
+    ///
+    /// # Errors
+    /// This function will return an error if driving `nreset` or
+    /// communication with I2C fails.
+    pub fn new_sparkfun_with_nreset<P, D>(
+        i2c: I2C,
+        nreset: &mut P,
+        delay: &mut D,
+    ) -> Result<Self, error::NresetError<E, P::Error>>
+    where
+        P: OutputPin,
+        D: DelayNs,
+    {
+        Self::new_with_nreset(
+            i2c,
+            SPARKFUN_ADDRESS,
+            clock::ClockConfig::new().led_divider(1),
+            nreset,
+            delay,
+        )
+    }
+
+    /// Reset the device by writing the reset sequence to `RegReset`.
+    fn soft_reset(i2c: &mut I2C::Bus, address: u8) -> Result<(), E> {
         i2c.write(address, &[reg::Register::RegReset as u8, 0x12])?;
-        i2c.write(address, &[reg::Register::RegReset as u8, 0x34])?;
+        i2c.write(address, &[reg::Register::RegReset as u8, 0x34])
+    }
 
-        // Enable internal 2MHz oscillator.
-        i2c.write(address, &[reg::Register::RegClock as u8, 0b0100_0000])?;
+    /// Set up the clock of a freshly reset device and wrap it in a driver.
+    fn from_reset(mut i2c: I2C::Mutex, address: u8, clock: clock::ClockConfig) -> Result<Self, E> {
+        let bus = I2C::get_mut(&mut i2c);
+        // `RegMisc` is otherwise all zeroes after reset.
+        bus.write(
+            address,
+            &[reg::Register::RegClock as u8, clock.clock_bits()],
+        )?;
+        bus.write(address, &[reg::Register::RegMisc as u8, clock.misc_bits()])?;
 
         Ok(Self {
-            interface: Interface::new(spin::Mutex::new(i2c), address),
+            interface: Interface::new(i2c, address, &clock),
         })
     }
 
@@ -48,7 +334,9 @@ where
     /// chip.
     ///
     /// # Errors
-    /// This function will return an error if communication with I2C fails.
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](error::Error::OscillatorOff) if the oscillator isn't
+    /// running.
     pub fn set_debounce_time(
         &mut self,
         debounce_time: DebounceTime,
@@ -56,34 +344,764 @@ where
         self.interface.set_debounce_time(debounce_time)
     }
 
+    /// Set the debounce time closest to `target`, for the current clock
+    /// frequency. This will affect all pins on the chip. Returns the actual
+    /// debounce time, which ranges from 0.5ms to 64ms with the internal
+    /// oscillator.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](error::Error::OscillatorOff) if the oscillator isn't
+    /// running.
+    pub fn set_debounce_duration(
+        &mut self,
+        target: impl time::IntoDuration,
+    ) -> Result<Duration, error::Error<E>> {
+        self.interface.set_debounce_duration(target.into_duration())
+    }
+
+    /// Read the debounce time from the chip, converted with the current clock
+    /// frequency. Returns `None` if the oscillator is off.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn debounce_duration(&mut self) -> Result<Option<Duration>, error::Error<E>> {
+        self.interface.debounce_duration()
+    }
+
+    /// Put the bank A pins selected by `mask` (bit 0 is `a0`) into LED driver
+    /// mode in one go. This disables their input buffers and pull-ups, makes
+    /// them open-drain outputs, enables the LED driver and starts the LED
+    /// clock if it isn't already running.
+    ///
+    /// This is much cheaper than configuring each LED individually when
+    /// driving many channels.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn enable_led_driver_bank_a(&mut self, mask: u8) -> Result<(), error::Error<E>> {
+        self.interface.enable_led_driver_bank(Bank::A, mask)
+    }
+
+    /// Put the bank B pins selected by `mask` (bit 0 is `b0`) into LED driver
+    /// mode in one go. See
+    /// [`enable_led_driver_bank_a`](Self::enable_led_driver_bank_a).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn enable_led_driver_bank_b(&mut self, mask: u8) -> Result<(), error::Error<E>> {
+        self.interface.enable_led_driver_bank(Bank::B, mask)
+    }
+
+    /// Set the outputs of bank A to `data` in one transaction, where bit 0 is
+    /// `a0`, for driving the bank as a parallel port such as an LED bar or a
+    /// data bus. Only pins that are outputs change, the rest just latch
+    /// their bit for when they become outputs. While the pins are split, use
+    /// [`Settings::write_bank_a`](settings::Settings::write_bank_a) instead.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn write_bank_a(&mut self, data: u8) -> Result<(), error::Error<E>> {
+        self.interface.write_bank_data(Bank::A, data)
+    }
+
+    /// Set the outputs of bank B to `data` in one transaction, where bit 0 is
+    /// `b0`. See [`write_bank_a`](Self::write_bank_a).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn write_bank_b(&mut self, data: u8) -> Result<(), error::Error<E>> {
+        self.interface.write_bank_data(Bank::B, data)
+    }
+
+    /// Set the outputs of both banks to `data` in one transaction, where bit
+    /// 0 is `a0` and bit 15 is `b7`, treating the chip as a 16-bit port. A
+    /// bank whose outputs are already known to hold its half of `data` isn't
+    /// written, so changing only one bank costs a single register write. See
+    /// [`write_bank_a`](Self::write_bank_a).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn write_port(&mut self, data: u16) -> Result<(), error::Error<E>> {
+        self.interface.write_data(data)
+    }
+
+    /// Read the level of every pin in one transaction, where bit 0 is `a0`
+    /// and bit 15 is `b7`. Both banks are read in the same burst, so the
+    /// result is a coherent snapshot of something like a DIP switch or a set
+    /// of buttons, rather than 16 reads taken at different times. While the
+    /// pins are split, use
+    /// [`Settings::read_port`](settings::Settings::read_port) instead.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn read_port(&mut self) -> Result<u16, error::Error<E>> {
+        self.interface.get_data_mask()
+    }
+
+    /// Set what a low pulse on the NRESET pin does. Use
+    /// [`NresetFunction::LedSync`] together with [`led::sync`] to keep
+    /// blinking LEDs phase-aligned.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_nreset_function(&mut self, function: NresetFunction) -> Result<(), error::Error<E>> {
+        self.interface.set_nreset_function(function)
+    }
+
+    /// Select where the chip's clock comes from. The driver starts the
+    /// internal oscillator when it is created, so this is only needed for an
+    /// external clock on OSCIO, or to stop the clock entirely. The frequency
+    /// is remembered for converting register values into durations.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_clock_source(&mut self, source: clock::ClockSource) -> Result<(), error::Error<E>> {
+        self.interface.set_clock_source(source)
+    }
+
+    /// Reconfigure how the chip is clocked, replacing the configuration it was
+    /// [created with](Self::new_with_clock). The new frequency is used for
+    /// any later timing conversions, but durations already written to the
+    /// chip scale with it. The LED driver clock is set too, so give the
+    /// config an [LED divider](clock::ClockConfig::led_divider) if LEDs are
+    /// in use.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_clock_config(&mut self, config: clock::ClockConfig) -> Result<(), error::Error<E>> {
+        self.interface.set_clock_config(&config)
+    }
+
+    /// Give back the I2C bus, or the lock it was passed in, so it can be
+    /// handed to other drivers or shut down for deep sleep. The chip keeps
+    /// its configuration, so put it in the state it should be left in first,
+    /// such as with [`set_safe_defaults`](Self::set_safe_defaults),
+    /// [`reset`](Self::reset) or [`enter_low_power`](Self::enter_low_power).
+    /// Anything held back since [`start_batch`](Self::start_batch) is
+    /// dropped.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::Sx1509;
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// expander.set_safe_defaults()?;
+    /// let i2c = expander.release();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn release(self) -> I2C {
+        self.interface.release()
+    }
+
+    /// Read the counts of I2C transfers made since the driver was created or
+    /// [`reset_stats`](Self::reset_stats) was last called. The counts are
+    /// only kept with the `stats` feature.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::{Pins, Sx1509};
+    /// # use embedded_hal::digital::OutputPin;
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// expander.reset_stats();
+    /// # let Pins { a0, .. } = expander.split();
+    /// # let mut led = a0.into_output()?;
+    /// led.set_high()?;
+    /// let stats = expander.stats();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        self.interface.stats()
+    }
+
+    /// Set the counts of I2C transfers back to `0`.
+    pub fn reset_stats(&mut self) {
+        self.interface.reset_stats();
+    }
+
+    /// Reset the device in software, putting every register back to its
+    /// power-on value, including the clock, which is left off. No pins can
+    /// exist while the driver is borrowed, so there is no stale pin state.
+    /// Use [`reset_with_clock`](Self::reset_with_clock) to set the clock up
+    /// again straight away.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn reset(&mut self) -> Result<(), error::Error<E>> {
+        self.interface.reset()
+    }
+
+    /// Reset the device like [`reset`](Self::reset), then clock it according
+    /// to `clock`, as [`new_with_clock`](Self::new_with_clock) would.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn reset_with_clock(&mut self, clock: clock::ClockConfig) -> Result<(), error::Error<E>> {
+        self.interface.reset()?;
+        self.interface.set_clock_config(&clock)
+    }
+
+    /// Put every pin back into a plain input, as after a reset, without
+    /// touching the clock or any other chip setting. The pin configuration
+    /// registers are restored to their power-on values, the LED driver is
+    /// turned off and debouncing is disabled on every pin, in three
+    /// transactions. Interrupt masks and edge sensitivity are left as they
+    /// are.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_safe_defaults(&mut self) -> Result<(), error::Error<E>> {
+        self.interface.set_safe_defaults()
+    }
+
+    /// Read `register` straight from the chip, for features the driver
+    /// doesn't cover. While the pins are split, use
+    /// [`Settings::read_register`](settings::Settings::read_register)
+    /// instead.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    #[cfg(feature = "raw")]
+    pub fn read_register(&mut self, register: reg::Register) -> Result<u8, error::Error<E>> {
+        self.interface.read_register(register)
+    }
+
+    /// Write `data` to `register` straight to the chip, for features the
+    /// driver doesn't cover. This bypasses the pin typestates, so changing
+    /// the configuration of a pin in use can leave it configured differently
+    /// from what its type says. Use [`reset`](Self::reset) rather than
+    /// writing the reset register, and
+    /// [`set_clock_config`](Self::set_clock_config) rather than writing the
+    /// clock registers, so the driver keeps track of them. While the pins
+    /// are split, use
+    /// [`Settings::write_register`](settings::Settings::write_register)
+    /// instead.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::Sx1509;
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # #[cfg(feature = "raw")]
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// use sx1509_eh::reg::Register;
+    ///
+    /// expander.write_register(Register::RegPolarityA, 0b0000_0001)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    #[cfg(feature = "raw")]
+    pub fn write_register(
+        &mut self,
+        register: reg::Register,
+        data: u8,
+    ) -> Result<(), error::Error<E>> {
+        self.interface.write_register(register, data)
+    }
+
+    /// Read the whole register map in one transaction, where index `i` of the
+    /// result holds the register at address `i`, for diagnostics or for
+    /// saving a snapshot of the configuration. Reading doesn't clear the
+    /// interrupt or event bits. While the pins are split, use
+    /// [`Settings::dump_registers`](settings::Settings::dump_registers)
+    /// instead.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn dump_registers(&mut self) -> Result<[u8; REGISTER_COUNT], error::Error<E>> {
+        self.interface.dump_registers()
+    }
+
+    /// Read the configuration of `PIN` back from the chip, where `0` is `a0`
+    /// and `15` is `b7`, for debugging or for checking what the chip actually
+    /// holds. While the pins are split, use
+    /// [`Settings::pin_config`](settings::Settings::pin_config) instead.
+    /// Using a pin number above 15 fails to compile.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn pin_config<const PIN: u8>(&mut self) -> Result<PinConfig, error::Error<E>> {
+        const { assert!(PIN < 16) };
+        self.interface.pin_config::<PIN>()
+    }
+
+    /// Borrow the I2C bus for the duration of `f`, to talk to another device
+    /// on the same bus without giving up the driver. The driver keeps a copy
+    /// of the SX1509's configuration registers and isn't told about anything
+    /// done on the bus, so don't use `f` to change them. While the pins are
+    /// split, use [`Settings::with_bus`](settings::Settings::with_bus)
+    /// instead.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::Sx1509;
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # struct Sensor;
+    /// # impl Sensor {
+    /// #     fn read<I: embedded_hal::i2c::I2c>(&self, _: &mut I) -> Result<u16, I::Error> {
+    /// #         Ok(0)
+    /// #     }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, sensor: Sensor) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// let temperature = expander.with_bus(|i2c| sensor.read(i2c))??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return [`BusBusy`](error::Error::BusBusy) if the
+    /// bus is already in use.
+    pub fn with_bus<R>(
+        &mut self,
+        f: impl FnOnce(&mut I2C::Bus) -> R,
+    ) -> Result<R, error::Error<E>> {
+        self.interface.with_bus(f)
+    }
+
+    /// Start holding back configuration writes until [`flush`](Self::flush),
+    /// to cut down I2C traffic when setting up many pins at boot. Calls made
+    /// meanwhile only change the driver's copy of the configuration
+    /// registers, and reads see the pending values. Writes to other
+    /// registers, such as the LED and reset registers, still go out straight
+    /// away. While the pins are split, use
+    /// [`Settings::start_batch`](settings::Settings::start_batch) instead.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::{Pins, Sx1509};
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// let Pins { a0, b0, mut settings, .. } = expander.split();
+    /// settings.start_batch();
+    /// let button = a0.into_input()?.pullup()?;
+    /// let mut led = b0.into_output()?;
+    /// settings.flush()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_batch(&mut self) {
+        self.interface.start_batch();
+    }
+
+    /// Write everything held back since [`start_batch`](Self::start_batch)
+    /// in as few bursts as possible, and stop batching.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    /// Anything not yet written stays pending and batching stays on, so the
+    /// flush can be retried.
+    pub fn flush(&mut self) -> Result<(), error::Error<E>> {
+        self.interface.flush()
+    }
+
+    /// Retry failed I2C transfers according to `policy`, so transient NACKs
+    /// on long or noisy buses don't surface as errors. Only the last error
+    /// is returned if every try fails. By default, transfers aren't retried.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.interface.set_retry_policy(policy);
+    }
+
+    /// Wait for the shared bus to be unlocked instead of failing with
+    /// [`BusBusy`](error::Error::BusBusy) when a pin is used while another is
+    /// mid-transfer. It is off by default.
+    ///
+    /// Only turn this on when the driver is used from threads or tasks that
+    /// can't preempt each other forever. An interrupt handler that waits for
+    /// the code it interrupted will spin forever on a single core. Only
+    /// locks that can be waited on, such as the [`SpinLock`](lock::SpinLock),
+    /// are affected.
+    pub fn set_blocking_lock(&mut self, enabled: bool) {
+        self.interface.set_blocking_lock(enabled);
+    }
+
+    /// Read back every register write and compare it, failing with
+    /// [`VerifyFailed`](error::Error::VerifyFailed) on a mismatch. This
+    /// catches bus corruption or an address conflict early on noisy buses, at
+    /// the cost of an extra read per write. It is off by default.
+    pub fn set_verify_writes(&mut self, enabled: bool) {
+        self.interface.set_verify_writes(enabled);
+    }
+
+    /// Read and decode `RegMisc`, which holds the LED fade modes, the LED
+    /// driver clock divider, the NRESET function and whether NINT is cleared
+    /// automatically.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn misc_config(&mut self) -> Result<MiscConfig, error::Error<E>> {
+        self.interface.misc_config()
+    }
+
+    /// Write all of `RegMisc` at once. See
+    /// [`update_misc_config`](Self::update_misc_config) to change only some
+    /// fields.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_misc_config(&mut self, config: MiscConfig) -> Result<(), error::Error<E>> {
+        self.interface.set_misc_config(config)
+    }
+
+    /// Read `RegMisc`, let `update` change it and write it back.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::{led::FadeMode, Sx1509};
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// expander.update_misc_config(|misc| misc.fade_mode_a = FadeMode::Logarithmic)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn update_misc_config(
+        &mut self,
+        update: impl FnOnce(&mut MiscConfig),
+    ) -> Result<(), error::Error<E>> {
+        let mut config = self.interface.misc_config()?;
+        update(&mut config);
+        self.interface.set_misc_config(config)
+    }
+
+    /// Read how the chip is clocked, such as when adopting a chip configured
+    /// by someone else or debugging timing. The chip can't tell the frequency
+    /// of an [external clock](clock::ClockSource::External), so that is the
+    /// one last given to the driver, or `0` if there wasn't one.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn clock_config(&mut self) -> Result<clock::ClockConfig, error::Error<E>> {
+        self.interface.clock_config()
+    }
+
+    /// Stop the chip's clock to cut its dynamic current when debouncing, the
+    /// keypad engine and the LED driver aren't needed. Debouncing and the
+    /// keypad engine then refuse to be configured with
+    /// [`OscillatorOff`](error::Error::OscillatorOff), and
+    /// [`led_timing`](Self::led_timing) returns `None`. Plain inputs,
+    /// outputs and interrupts keep working. Use
+    /// [`set_clock_source`](Self::set_clock_source) to start it again.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn disable_oscillator(&mut self) -> Result<(), error::Error<E>> {
+        self.interface.set_clock_source(clock::ClockSource::Off)
+    }
+
+    /// Drive the chip's clock out of OSCIO, divided by `2^divider`, to clock
+    /// other parts of the board. Returns the resulting frequency in Hz, which
+    /// is `0` if the clock is off. OSCIO can't be an output while it is the
+    /// [external clock input](clock::ClockSource::External).
+    ///
+    /// # Errors
+    /// This function will return
+    /// [`DividerOutOfRange`](error::Error::DividerOutOfRange) if `divider` is
+    /// not below 14, or an error if communication with I2C fails.
+    pub fn set_clock_output(&mut self, divider: u8) -> Result<u32, error::Error<E>> {
+        self.interface.set_clock_output(divider)
+    }
+
+    /// Get the [`LedTiming`](led::LedTiming) for the current LED driver clock,
+    /// to convert durations into blink and fade register values. Returns
+    /// `None` if the LED driver clock or the oscillator is off.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn led_timing(&mut self) -> Result<Option<led::LedTiming>, error::Error<E>> {
+        Ok(self.interface.led_clock_hz()?.map(led::LedTiming::new))
+    }
+
+    /// Read which pins have triggered an interrupt, where bit 0 is `a0` and
+    /// bit 15 is `b7`. Both banks are read in a single transaction. The bits
+    /// stay set until they are cleared.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn interrupt_source(&mut self) -> Result<u16, error::Error<E>> {
+        self.interface.interrupt_source()
+    }
+
+    /// Turn off the input buffers of the pins in `mask` in one transaction,
+    /// where bit 0 is `a0` and bit 15 is `b7`, and turn the rest back on. A
+    /// floating line on an enabled buffer draws current, so turn it off on
+    /// unused and output-only pins in battery powered devices. Inputs can't
+    /// be read while their buffer is off, but it is turned back on when a pin
+    /// is made an input. The LED driver needs the buffers of its pins off, so
+    /// keep them in `mask`.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_input_disable(&mut self, mask: u16) -> Result<(), error::Error<E>> {
+        self.interface.set_input_disable_mask(mask)
+    }
+
+    /// Slow down the edges of the outputs in `mask` in one transaction, where
+    /// bit 0 is `a0` and bit 15 is `b7`, and bring the rest back to full
+    /// speed. See [`Output::set_long_slew`].
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_long_slew(&mut self, mask: u16) -> Result<(), error::Error<E>> {
+        self.interface.set_long_slew_mask(mask)
+    }
+
+    /// Reduce the drive strength of the outputs in `mask` in one transaction,
+    /// where bit 0 is `a0` and bit 15 is `b7`, and bring the rest back to
+    /// full strength. See [`Output::set_low_drive`].
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_low_drive(&mut self, mask: u16) -> Result<(), error::Error<E>> {
+        self.interface.set_low_drive_mask(mask)
+    }
+
+    /// Let the inputs in `mask` accept logic levels above their bank's supply,
+    /// up to 5.5V, in one transaction, where bit 0 is `a0` and bit 15 is
+    /// `b7`, and turn it off for the rest. See [`Input::set_high_input`].
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_high_input(&mut self, mask: u16) -> Result<(), error::Error<E>> {
+        self.interface.set_high_input_mask(mask)
+    }
+
+    /// Apply `profile` to every pin in `mask`, where bit 0 is `a0` and bit 15
+    /// is `b7`, leaving the other pins as they are. See
+    /// [`Output::set_drive_profile`].
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_drive_profile(
+        &mut self,
+        mask: u16,
+        profile: drive::DriveProfile,
+    ) -> Result<(), error::Error<E>> {
+        self.interface.set_drive_profile(mask, profile)
+    }
+
+    /// Invert the pins in `mask` in hardware in one transaction, where bit 0
+    /// is `a0` and bit 15 is `b7`, and restore the rest to normal polarity.
+    /// See [`Input::set_inverted`] and [`Output::set_inverted`].
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_polarity(&mut self, mask: u16) -> Result<(), error::Error<E>> {
+        self.interface.set_polarity_mask(mask)
+    }
+
+    /// Set which pins are masked as interrupt sources in one transaction,
+    /// where bit 0 is `a0` and bit 15 is `b7`. A set bit masks the pin, so it
+    /// can't assert NINT, and a clear bit lets it. Every pin is masked after
+    /// reset.
+    ///
+    /// This writes the whole register at once, so it doesn't update the
+    /// [`InterruptOn`](states::InterruptOn) typestate of any inputs.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_interrupt_mask(&mut self, mask: u16) -> Result<(), error::Error<E>> {
+        self.interface.set_interrupt_mask(mask)
+    }
+
+    /// Set the edge sensitivity of every pin in one transaction, where bit 0
+    /// is `a0` and bit 15 is `b7`. Pins in `rising` sense rising edges, pins
+    /// in `falling` sense falling edges, pins in both sense both edges, and
+    /// pins in neither sense nothing.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_sense(&mut self, rising: u16, falling: u16) -> Result<(), error::Error<E>> {
+        self.interface.set_sense(rising, falling)
+    }
+
+    /// Park the chip in its lowest power state until one of the wake pins
+    /// asserts NINT. Only the pins in `rising` and `falling` stay unmasked,
+    /// sensing those edges, and the keypad engine, LED driver clock and
+    /// oscillator are stopped. The wake pins should already be inputs.
+    ///
+    /// Debouncing needs the oscillator, so it is turned off on the wake pins.
+    /// Everything changed is saved in the returned state, which
+    /// [`exit_low_power`](Self::exit_low_power) restores once the MCU has
+    /// woken up.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::Sx1509;
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C, button_mask: u16) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// let state = expander.enter_low_power(0, button_mask)?;
+    /// // Sleep the MCU until NINT goes low.
+    /// expander.exit_low_power(&state)?;
+    /// let woken_by = expander.service_nint()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn enter_low_power(
+        &mut self,
+        rising: u16,
+        falling: u16,
+    ) -> Result<LowPowerState, error::Error<E>> {
+        self.interface.enter_low_power(rising, falling)
+    }
+
+    /// Restore the settings changed by
+    /// [`enter_low_power`](Self::enter_low_power). Interrupts are left
+    /// pending, so the pin that woke the chip can still be read.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn exit_low_power(&mut self, state: &LowPowerState) -> Result<(), error::Error<E>> {
+        self.interface.exit_low_power(state)
+    }
+
+    /// Read which pins have a pending interrupt, as an iterator over their
+    /// numbers. The interrupts aren't cleared.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::Sx1509;
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// for pin in expander.pending_interrupts()? {
+    ///     // ...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn pending_interrupts(&mut self) -> Result<interrupt::PendingInterrupts, error::Error<E>> {
+        Ok(self.interface.interrupt_source()?.into())
+    }
+
+    /// Clear the interrupt and latched event bits of the pins in `mask`, where
+    /// bit 0 is `a0` and bit 15 is `b7`. NINT is released once no interrupt
+    /// bits are left set.
+    ///
+    /// By default the chip also clears a bank's interrupts whenever its data
+    /// register is read, for example by reading an input, see
+    /// [`set_nint_autoclear`](Self::set_nint_autoclear). Clearing explicitly
+    /// releases NINT at a known point regardless.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn clear_interrupts(&mut self, mask: u16) -> Result<(), error::Error<E>> {
+        self.interface.clear_interrupts(mask)
+    }
+
+    /// Read which pins triggered an interrupt and clear them, releasing NINT,
+    /// where bit 0 is `a0` and bit 15 is `b7`. This takes one burst read,
+    /// plus one burst write if anything triggered, so it suits code running
+    /// right after NINT asserts. Use the
+    /// [`InterruptHandler`](interrupt::InterruptHandler) instead to share the
+    /// interrupt between several users.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn service_nint(&mut self) -> Result<u16, error::Error<E>> {
+        let triggered = self.interface.interrupt_source()?;
+        if triggered != 0 {
+            self.interface.clear_interrupts(triggered)?;
+        }
+        Ok(triggered)
+    }
+
+    /// Set whether reading a bank's data register clears its interrupts and
+    /// releases NINT, which is on after reset. Turn it off to make
+    /// [`clear_interrupts`](Self::clear_interrupts) the only way to release
+    /// NINT, so reading inputs can't drop an interrupt before it's handled.
+    /// This matters with level-sensitive MCU interrupt inputs.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_nint_autoclear(&mut self, enabled: bool) -> Result<(), error::Error<E>> {
+        self.interface.set_nint_autoclear(enabled)
+    }
+
+    /// Read and clear the latched event bits, where bit 0 is `a0` and bit 15
+    /// is `b7`. A pin's bit is set when an edge matching its sense setting is
+    /// seen, whether or not it is masked as an interrupt, and stays set until
+    /// cleared. This catches pulses too short to see by polling the inputs.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn take_events(&mut self) -> Result<u16, error::Error<E>> {
+        self.interface.take_events()
+    }
+
     /// Split the expander into individual pins. This allows you to configure
     /// each pin as an input or output. A mutable reference is used to ensure
-    /// multiple sets of pins cannot exist at the same time.
+    /// multiple sets of pins cannot exist at the same time. Chip-wide settings
+    /// can still be changed through [`Pins::settings`].
+    ///
+    /// The pins only borrow the expander, so handing them back is a matter of
+    /// dropping them, along with [`Pins::settings`] and [`Pins::interrupts`].
+    /// Once none of them are in use, the expander can be used directly again
+    /// and split anew, which suits designs that reconfigure every pin when
+    /// switching between application modes.
+    ///
+    /// ```rust
+    /// # use sx1509_eh::{Pins, Sx1509};
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// let Pins { a0, .. } = expander.split();
+    /// let led = a0.into_output()?;
+    /// // ...
+    /// drop(led);
+    ///
+    /// expander.set_safe_defaults()?;
+    /// let Pins { a0, .. } = expander.split();
+    /// let button = a0.into_input()?;
+    /// # Ok(())
+    /// # }
+    /// ```
     pub fn split(&mut self) -> Pins<'_, I2C> {
-        Pins {
-            a0: Pin::new(&self.interface),
-            a1: Pin::new(&self.interface),
-            a2: Pin::new(&self.interface),
-            a3: Pin::new(&self.interface),
-            a4: Pin::new(&self.interface),
-            a5: Pin::new(&self.interface),
-            a6: Pin::new(&self.interface),
-            a7: Pin::new(&self.interface),
-
-            b0: Pin::new(&self.interface),
-            b1: Pin::new(&self.interface),
-            b2: Pin::new(&self.interface),
-            b3: Pin::new(&self.interface),
-            b4: Pin::new(&self.interface),
-            b5: Pin::new(&self.interface),
-            b6: Pin::new(&self.interface),
-            b7: Pin::new(&self.interface),
-        }
+        Pins::new(&self.interface)
     }
 }
 
 /// The pins on the SX1509.
-pub struct Pins<'a, I2C> {
+pub struct Pins<'a, I2C: BusLock> {
     /// Bank A, Pin 0
     pub a0: Pin<'a, 0, I2C>,
     /// Bank A, Pin 1
@@ -117,4 +1135,46 @@ pub struct Pins<'a, I2C> {
     pub b6: Pin<'a, 14, I2C>,
     /// Bank B, Pin 7
     pub b7: Pin<'a, 15, I2C>,
+
+    /// The OSCIO pin, as a general-purpose output.
+    pub oscio: clock::Oscio<'a, I2C>,
+
+    /// The chip-wide settings, which can be changed while the pins are in use.
+    pub settings: settings::Settings<'a, I2C>,
+
+    /// The interrupt handler for the pins.
+    pub interrupts: interrupt::InterruptHandler<'a, I2C>,
+}
+
+impl<'a, I2C, E> Pins<'a, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    pub(crate) fn new(interface: &'a Interface<I2C>) -> Self {
+        Self {
+            a0: Pin::new(interface),
+            a1: Pin::new(interface),
+            a2: Pin::new(interface),
+            a3: Pin::new(interface),
+            a4: Pin::new(interface),
+            a5: Pin::new(interface),
+            a6: Pin::new(interface),
+            a7: Pin::new(interface),
+
+            b0: Pin::new(interface),
+            b1: Pin::new(interface),
+            b2: Pin::new(interface),
+            b3: Pin::new(interface),
+            b4: Pin::new(interface),
+            b5: Pin::new(interface),
+            b6: Pin::new(interface),
+            b7: Pin::new(interface),
+
+            oscio: clock::Oscio::new(interface),
+
+            settings: settings::Settings::new(interface),
+
+            interrupts: interrupt::InterruptHandler::new(interface),
+        }
+    }
 }
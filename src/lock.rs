@@ -0,0 +1,224 @@
+use core::cell::RefCell;
+
+/// A lock guarding the I2C bus shared by the driver and its pins. A plain
+/// I2C bus is guarded by the [`DefaultLock`]. To pick another one, wrap the
+/// bus in [`SpinLock`], [`RefCellLock`] or, with the `critical-section`
+/// feature, `CriticalSectionLock` before passing it to a constructor, or
+/// implement this trait for the mutex of an RTOS.
+///
+/// ```rust
+/// # use sx1509_eh::{lock::RefCellLock, Sx1509};
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// let expander = Sx1509::new(RefCellLock::new(i2c), 0x3E)?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait BusLock {
+    /// The error type of the bus.
+    type Error: embedded_hal::i2c::Error;
+    /// The I2C bus being guarded.
+    type Bus: embedded_hal::i2c::I2c<Error = Self::Error>;
+    /// What the driver keeps to lock the bus. This is `Self` for the locks
+    /// themselves, and the [`DefaultLock`] for a plain I2C bus.
+    type Mutex;
+
+    /// Turn the value passed to a constructor into the lock the driver keeps.
+    fn into_mutex(self) -> Self::Mutex;
+
+    /// Turn the lock the driver kept back into the value passed to its
+    /// constructor.
+    fn from_mutex(mutex: Self::Mutex) -> Self;
+
+    /// Get the bus without locking, as the driver has `mutex` to itself.
+    fn get_mut(mutex: &mut Self::Mutex) -> &mut Self::Bus;
+
+    /// Run `f` with the bus, or return `None` if it is already locked.
+    fn try_lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R>;
+
+    /// Run `f` with the bus, waiting for it to be unlocked if the lock can be
+    /// waited on. Used instead of [`try_lock`](Self::try_lock) with
+    /// [`Sx1509::set_blocking_lock`](crate::Sx1509::set_blocking_lock). By
+    /// default this doesn't wait.
+    fn lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R> {
+        Self::try_lock(mutex, f)
+    }
+}
+
+/// The lock used for a plain I2C bus. Features don't change it, so that
+/// enabling one can't make the driver lose `Sync` for other crates using it.
+pub type DefaultLock<I2C> = SpinLock<I2C>;
+
+impl<I2C> BusLock for I2C
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    type Error = I2C::Error;
+    type Bus = I2C;
+    type Mutex = DefaultLock<I2C>;
+
+    fn into_mutex(self) -> Self::Mutex {
+        DefaultLock::new(self)
+    }
+
+    fn from_mutex(mutex: Self::Mutex) -> Self {
+        mutex.into_inner()
+    }
+
+    fn get_mut(mutex: &mut Self::Mutex) -> &mut Self::Bus {
+        <DefaultLock<I2C> as BusLock>::get_mut(mutex)
+    }
+
+    fn try_lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R> {
+        <DefaultLock<I2C> as BusLock>::try_lock(mutex, f)
+    }
+
+    fn lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R> {
+        <DefaultLock<I2C> as BusLock>::lock(mutex, f)
+    }
+}
+
+/// A spin lock. It can be shared between threads and interrupt handlers, but
+/// using a pin while another is mid-transfer fails with
+/// [`BusBusy`](crate::error::Error::BusBusy), and waiting for it from an
+/// interrupt handler can spin forever on a single core.
+pub struct SpinLock<I2C>(spin::Mutex<I2C>);
+
+impl<I2C> SpinLock<I2C> {
+    /// Guard `i2c` with a spin lock.
+    pub const fn new(i2c: I2C) -> Self {
+        Self(spin::Mutex::new(i2c))
+    }
+
+    /// Get the bus back.
+    pub fn into_inner(self) -> I2C {
+        self.0.into_inner()
+    }
+}
+
+impl<I2C> BusLock for SpinLock<I2C>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    type Error = I2C::Error;
+    type Bus = I2C;
+    type Mutex = Self;
+
+    fn into_mutex(self) -> Self::Mutex {
+        self
+    }
+
+    fn from_mutex(mutex: Self::Mutex) -> Self {
+        mutex
+    }
+
+    fn get_mut(mutex: &mut Self::Mutex) -> &mut Self::Bus {
+        mutex.0.get_mut()
+    }
+
+    fn try_lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R> {
+        let mut i2c = mutex.0.try_lock()?;
+        Some(f(&mut i2c))
+    }
+
+    fn lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R> {
+        Some(f(&mut mutex.0.lock()))
+    }
+}
+
+/// A plain [`RefCell`], for firmware that only uses the driver from a single
+/// context. It only fails with
+/// [`BusBusy`](crate::error::Error::BusBusy) when a pin is used inside
+/// [`Sx1509::with_bus`](crate::Sx1509::with_bus).
+pub struct RefCellLock<I2C>(RefCell<I2C>);
+
+impl<I2C> RefCellLock<I2C> {
+    /// Guard `i2c` with a [`RefCell`].
+    pub const fn new(i2c: I2C) -> Self {
+        Self(RefCell::new(i2c))
+    }
+
+    /// Get the bus back.
+    pub fn into_inner(self) -> I2C {
+        self.0.into_inner()
+    }
+}
+
+impl<I2C> BusLock for RefCellLock<I2C>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    type Error = I2C::Error;
+    type Bus = I2C;
+    type Mutex = Self;
+
+    fn into_mutex(self) -> Self::Mutex {
+        self
+    }
+
+    fn from_mutex(mutex: Self::Mutex) -> Self {
+        mutex
+    }
+
+    fn get_mut(mutex: &mut Self::Mutex) -> &mut Self::Bus {
+        mutex.0.get_mut()
+    }
+
+    fn try_lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R> {
+        let mut i2c = mutex.0.try_borrow_mut().ok()?;
+        Some(f(&mut i2c))
+    }
+}
+
+/// A [`critical-section`](critical_section) mutex. Every transfer runs with
+/// interrupts disabled, so it is safe to use from interrupt handlers on
+/// single-core MCUs, and only fails with
+/// [`BusBusy`](crate::error::Error::BusBusy) when a pin is used inside
+/// [`Sx1509::with_bus`](crate::Sx1509::with_bus).
+#[cfg(feature = "critical-section")]
+pub struct CriticalSectionLock<I2C>(critical_section::Mutex<RefCell<I2C>>);
+
+#[cfg(feature = "critical-section")]
+impl<I2C> CriticalSectionLock<I2C> {
+    /// Guard `i2c` with a critical section.
+    pub const fn new(i2c: I2C) -> Self {
+        Self(critical_section::Mutex::new(RefCell::new(i2c)))
+    }
+
+    /// Get the bus back.
+    pub fn into_inner(self) -> I2C {
+        self.0.into_inner().into_inner()
+    }
+}
+
+#[cfg(feature = "critical-section")]
+impl<I2C> BusLock for CriticalSectionLock<I2C>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    type Error = I2C::Error;
+    type Bus = I2C;
+    type Mutex = Self;
+
+    fn into_mutex(self) -> Self::Mutex {
+        self
+    }
+
+    fn from_mutex(mutex: Self::Mutex) -> Self {
+        mutex
+    }
+
+    fn get_mut(mutex: &mut Self::Mutex) -> &mut Self::Bus {
+        mutex.0.get_mut().get_mut()
+    }
+
+    fn try_lock<R>(mutex: &Self::Mutex, f: impl FnOnce(&mut Self::Bus) -> R) -> Option<R> {
+        critical_section::with(|cs| {
+            let mut i2c = mutex.0.borrow(cs).try_borrow_mut().ok()?;
+            Some(f(&mut i2c))
+        })
+    }
+}
@@ -0,0 +1,167 @@
+//! A fake SX1509 on a fake I2C bus, for the tests. The registers are kept in
+//! memory, and every write is logged so tests can check what the driver sent.
+
+extern crate std;
+
+use std::{cell::RefCell, rc::Rc, vec::Vec};
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+
+use crate::{clock::ClockConfig, lock::BusLock, Interface};
+
+/// The address the fake chip answers on.
+pub(crate) const ADDRESS: u8 = 0x3E;
+
+/// The registers that don't power up as zero.
+const POWER_ON: [(u8, u8); 8] = [
+    (0x0E, 0xFF), // RegDirB
+    (0x0F, 0xFF), // RegDirA
+    (0x10, 0xFF), // RegDataB
+    (0x11, 0xFF), // RegDataA
+    (0x12, 0xFF), // RegInterruptMaskB
+    (0x13, 0xFF), // RegInterruptMaskA
+    (0x27, 0xFF), // RegKeyData1
+    (0x28, 0xFF), // RegKeyData2
+];
+
+/// The state of the fake chip, shared between the bus and the test.
+struct Chip {
+    registers: [u8; 256],
+    /// Every write, as the register it started at and the data written.
+    writes: Vec<(u8, Vec<u8>)>,
+    /// How many transactions read from the chip.
+    reads: usize,
+    /// How many of the next transactions fail.
+    failures: usize,
+    /// Whether the first byte of the reset sequence has been written.
+    reset_armed: bool,
+}
+
+impl Chip {
+    fn power_on(&mut self) {
+        self.registers = [0; 256];
+        for (register, value) in POWER_ON {
+            self.registers[usize::from(register)] = value;
+        }
+    }
+
+    fn write(&mut self, start: u8, data: &[u8]) {
+        self.writes.push((start, data.to_vec()));
+        for (register, &value) in (start..).zip(data) {
+            match register {
+                // Writing ones clears interrupt and event bits.
+                0x18..=0x1B => self.registers[usize::from(register)] &= !value,
+                0x7D => {
+                    if self.reset_armed && value == 0x34 {
+                        self.power_on();
+                    }
+                    self.reset_armed = value == 0x12;
+                }
+                _ => self.registers[usize::from(register)] = value,
+            }
+        }
+    }
+}
+
+/// The fake bus. Clones share the same chip.
+#[derive(Clone)]
+pub(crate) struct MockBus(Rc<RefCell<Chip>>);
+
+impl MockBus {
+    pub(crate) fn new() -> Self {
+        let mut chip = Chip {
+            registers: [0; 256],
+            writes: Vec::new(),
+            reads: 0,
+            failures: 0,
+            reset_armed: false,
+        };
+        chip.power_on();
+        Self(Rc::new(RefCell::new(chip)))
+    }
+
+    /// The value of `register`.
+    pub(crate) fn register(&self, register: u8) -> u8 {
+        self.0.borrow().registers[usize::from(register)]
+    }
+
+    /// Set `register` as if the chip had changed it.
+    pub(crate) fn set_register(&self, register: u8, value: u8) {
+        self.0.borrow_mut().registers[usize::from(register)] = value;
+    }
+
+    /// Take the writes logged so far.
+    pub(crate) fn take_writes(&self) -> Vec<(u8, Vec<u8>)> {
+        core::mem::take(&mut self.0.borrow_mut().writes)
+    }
+
+    /// How many transactions have read from the chip.
+    pub(crate) fn reads(&self) -> usize {
+        self.0.borrow().reads
+    }
+
+    /// Make the next `count` transactions fail.
+    pub(crate) fn fail_next(&self, count: usize) {
+        self.0.borrow_mut().failures = count;
+    }
+
+    /// An interface for the chip, clocked by the internal oscillator, that
+    /// skips the reset the constructors do.
+    pub(crate) fn interface(&self) -> Interface<Self> {
+        Interface::new(self.clone().into_mutex(), ADDRESS, &ClockConfig::new())
+    }
+}
+
+impl ErrorType for MockBus {
+    type Error = ErrorKind;
+}
+
+impl I2c for MockBus {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut chip = self.0.borrow_mut();
+        if chip.failures > 0 {
+            chip.failures -= 1;
+            return Err(ErrorKind::Bus);
+        }
+        if address != ADDRESS {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+
+        // The first byte written sets the register pointer, and the rest of
+        // the transaction auto-increments it.
+        let mut pointer = None;
+        let mut written = Vec::new();
+        let mut read = false;
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => {
+                    let mut data = &data[..];
+                    if let (None, Some((&first, rest))) = (pointer, data.split_first()) {
+                        pointer = Some(first);
+                        data = rest;
+                    }
+                    written.extend_from_slice(data);
+                }
+                Operation::Read(buffer) => {
+                    let start = pointer.unwrap_or(0);
+                    for (register, byte) in (start..).zip(buffer.iter_mut()) {
+                        *byte = chip.registers[usize::from(register)];
+                    }
+                    read = true;
+                }
+            }
+        }
+
+        if let (Some(start), false) = (pointer, written.is_empty()) {
+            chip.write(start, &written);
+        }
+        if read {
+            chip.reads += 1;
+        }
+        Ok(())
+    }
+}
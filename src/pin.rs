@@ -4,32 +4,42 @@ use embedded_hal::digital::{ErrorType, InputPin, OutputPin, StatefulOutputPin};
 
 use crate::{
     error::{Error, ModeChange},
+    led::Led,
+    lock::BusLock,
     states, Interface,
 };
 
 /// A pin on the SX1509. Use [`into_output`](Self::into_output) or
 /// [`into_input`](Self::into_input) to configure the pin as an output or input,
 /// respectively.
-pub struct Pin<'a, const PIN: u8, I2C> {
-    interface: &'a Interface<I2C>,
+pub struct Pin<'a, const PIN: u8, I2C: BusLock> {
+    pub(crate) interface: &'a Interface<I2C>,
 }
 
 /// An output pin on the SX1509.
-pub struct Output<'a, const PIN: u8, I2C, S> {
+pub struct Output<'a, const PIN: u8, I2C: BusLock, S> {
     pub(crate) interface: &'a Interface<I2C>,
     pub(crate) _state: PhantomData<S>,
 }
 
+/// A pin parked in its lowest-leakage state: an input with its input buffer,
+/// pulls, debouncing and interrupt all off. Get one from
+/// [`Pin::into_disconnected`].
+pub struct Disconnected<'a, const PIN: u8, I2C: BusLock> {
+    pub(crate) interface: &'a Interface<I2C>,
+}
+
 /// An input pin on the SX1509.
-pub struct Input<'a, const PIN: u8, I2C, S, D> {
+pub struct Input<'a, const PIN: u8, I2C: BusLock, S, D, N> {
     pub(crate) interface: &'a Interface<I2C>,
     pub(crate) _state: PhantomData<S>,
     pub(crate) _debounce: PhantomData<D>,
+    pub(crate) _interrupt: PhantomData<N>,
 }
 
 impl<'a, const PIN: u8, I2C, E> Pin<'a, PIN, I2C>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     pub(crate) fn new(interface: &'a Interface<I2C>) -> Self {
         Self { interface }
@@ -46,11 +56,11 @@ where
     ) -> Result<Output<'a, PIN, I2C, states::PushPull>, ModeChange<Error<E>, Self>> {
         // This will be a lot neater when `try` blocks are stabilized.
 
-        let result = (|| -> Result<(), Error<E>> {
-            self.interface.set_output::<PIN>()?;
-            self.interface.set_open_drain::<PIN>(false)?;
+        let result = self.interface.coalesce(|stage| {
+            stage.set_output::<PIN>()?;
+            stage.set_open_drain::<PIN>(false)?;
             Ok(())
-        })();
+        });
 
         match result {
             Ok(()) => Ok(Output {
@@ -70,33 +80,117 @@ where
     pub fn into_input(
         self,
     ) -> Result<
-        Input<'a, PIN, I2C, states::Floating, states::DebounceOff>,
+        Input<'a, PIN, I2C, states::Floating, states::DebounceOff, states::InterruptOff>,
         ModeChange<Error<E>, Self>,
     > {
         // This will be a lot neater when `try` blocks are stabilized.
 
-        let result = (|| -> Result<(), Error<E>> {
-            self.interface.set_input::<PIN>()?;
-            self.interface.set_pull_up::<PIN>(false)?;
-            self.interface.set_pull_down::<PIN>(false)?;
-            self.interface.set_debounce_enable::<PIN>(false)?;
+        let result = self.interface.coalesce(|stage| {
+            stage.set_input::<PIN>()?;
+            stage.set_input_disable::<PIN>(false)?;
+            stage.set_pull_up::<PIN>(false)?;
+            stage.set_pull_down::<PIN>(false)?;
+            stage.set_debounce_enable::<PIN>(false)?;
             Ok(())
-        })();
+        });
 
         match result {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Configure the pin to use the LED driver. This makes the pin an
+    /// open-drain output with its input buffer disabled, and starts the LED
+    /// clock if it isn't already running.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the pin can be extracted from the
+    /// [`ModeChange`](ModeChange), although it may be partially configured.
+    pub fn into_led(self) -> Result<Led<'a, PIN, I2C>, ModeChange<Error<E>, Self>> {
+        match self.interface.enable_led_driver::<PIN>() {
+            Ok(()) => Ok(Led {
+                interface: self.interface,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
+
+    /// Park the pin in its lowest-leakage state, for pins that are unused.
+    /// The pin is made an input with its input buffer, pulls, debouncing and
+    /// interrupt turned off, so a floating line draws no current.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the pin can be extracted from the
+    /// [`ModeChange`](ModeChange), although it may be partially configured.
+    pub fn into_disconnected(
+        self,
+    ) -> Result<Disconnected<'a, PIN, I2C>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_input::<PIN>()?;
+            stage.set_pull_up::<PIN>(false)?;
+            stage.set_pull_down::<PIN>(false)?;
+            stage.set_debounce_enable::<PIN>(false)?;
+            stage.disable_interrupt::<PIN>()?;
+            stage.set_input_disable::<PIN>(true)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Disconnected {
+                interface: self.interface,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+}
+
+impl<'a, const PIN: u8, I2C, E> Disconnected<'a, PIN, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Reconnect the pin as an input, turning its input buffer back on.
+    ///
+    /// # Errors
+    /// See [`Pin::into_input`].
+    pub fn into_input(
+        self,
+    ) -> Result<
+        Input<'a, PIN, I2C, states::Floating, states::DebounceOff, states::InterruptOff>,
+        ModeChange<Error<E>, Self>,
+    > {
+        Pin::new(self.interface)
+            .into_input()
+            .map_err(|ModeChange { error, .. }| ModeChange { error, pin: self })
+    }
+
+    /// Reconnect the pin as an output, turning its input buffer back on.
+    ///
+    /// # Errors
+    /// See [`Pin::into_output`].
+    pub fn into_output(
+        self,
+    ) -> Result<Output<'a, PIN, I2C, states::PushPull>, ModeChange<Error<E>, Self>> {
+        if let Err(error) = self.interface.set_input_disable::<PIN>(false) {
+            return Err(ModeChange { error, pin: self });
+        }
+
+        Pin::new(self.interface)
+            .into_output()
+            .map_err(|ModeChange { error, .. }| ModeChange { error, pin: self })
+    }
 }
 
-impl<'a, const PIN: u8, I2C, E, S> OutputPin for Output<'a, PIN, I2C, S>
+impl<const PIN: u8, I2C, E, S> OutputPin for Output<'_, PIN, I2C, S>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
     E: core::fmt::Debug,
 {
     fn set_low(&mut self) -> Result<(), Self::Error> {
@@ -108,9 +202,9 @@ where
     }
 }
 
-impl<'a, const PIN: u8, I2C, E, S> StatefulOutputPin for Output<'a, PIN, I2C, S>
+impl<const PIN: u8, I2C, E, S> StatefulOutputPin for Output<'_, PIN, I2C, S>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
     E: core::fmt::Debug,
 {
     fn is_set_high(&mut self) -> Result<bool, Self::Error> {
@@ -120,11 +214,15 @@ where
     fn is_set_low(&mut self) -> Result<bool, Self::Error> {
         self.is_set_high().map(|v| !v)
     }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        self.interface.toggle_data::<PIN>()
+    }
 }
 
-impl<'a, const PIN: u8, I2C, E, S, D> InputPin for Input<'a, PIN, I2C, S, D>
+impl<const PIN: u8, I2C, E, S, D, N> InputPin for Input<'_, PIN, I2C, S, D, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
     E: core::fmt::Debug,
 {
     fn is_high(&mut self) -> Result<bool, Self::Error> {
@@ -136,17 +234,17 @@ where
     }
 }
 
-impl<'a, const PIN: u8, I2C, E, S> ErrorType for Output<'a, PIN, I2C, S>
+impl<const PIN: u8, I2C, E, S> ErrorType for Output<'_, PIN, I2C, S>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
     E: core::fmt::Debug,
 {
     type Error = Error<E>;
 }
 
-impl<'a, const PIN: u8, I2C, E, S, D> ErrorType for Input<'a, PIN, I2C, S, D>
+impl<const PIN: u8, I2C, E, S, D, N> ErrorType for Input<'_, PIN, I2C, S, D, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
     E: core::fmt::Debug,
 {
     type Error = Error<E>;
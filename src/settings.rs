@@ -0,0 +1,236 @@
+use core::time::Duration;
+
+use crate::{
+    clock::ClockConfig,
+    error::Error,
+    led::{LedTiming, NresetFunction},
+    lock::BusLock,
+    time::IntoDuration,
+    Bank, DebounceTime, Interface, PinConfig, Stats, REGISTER_COUNT,
+};
+
+/// The chip-wide settings of a split [`Sx1509`](crate::Sx1509), which can
+/// still be changed while the pins are in use. Get it from
+/// [`Sx1509::split`](crate::Sx1509::split).
+///
+/// ```rust
+/// # use sx1509_eh::{Pins, Sx1509};
+/// # use core::time::Duration;
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// let Pins { a0, mut settings, .. } = expander.split();
+/// let button = a0.into_input()?.pullup()?.debounce_on()?;
+/// settings.set_debounce_duration(Duration::from_millis(16))?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Settings<'a, I2C: BusLock> {
+    interface: &'a Interface<I2C>,
+}
+
+impl<'a, I2C: BusLock> Settings<'a, I2C> {
+    pub(crate) fn new(interface: &'a Interface<I2C>) -> Self {
+        Self { interface }
+    }
+}
+
+impl<I2C, E> Settings<'_, I2C>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// See [`Sx1509::set_debounce_time`](crate::Sx1509::set_debounce_time).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](Error::OscillatorOff) if the oscillator isn't
+    /// running.
+    pub fn set_debounce_time(&mut self, debounce_time: DebounceTime) -> Result<(), Error<E>> {
+        self.interface.set_debounce_time(debounce_time)
+    }
+
+    /// See
+    /// [`Sx1509::set_debounce_duration`](crate::Sx1509::set_debounce_duration).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](Error::OscillatorOff) if the oscillator isn't
+    /// running.
+    pub fn set_debounce_duration(
+        &mut self,
+        target: impl IntoDuration,
+    ) -> Result<Duration, Error<E>> {
+        self.interface.set_debounce_duration(target.into_duration())
+    }
+
+    /// See [`Sx1509::debounce_duration`](crate::Sx1509::debounce_duration).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn debounce_duration(&mut self) -> Result<Option<Duration>, Error<E>> {
+        self.interface.debounce_duration()
+    }
+
+    /// See [`Sx1509::set_nreset_function`](crate::Sx1509::set_nreset_function).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_nreset_function(&mut self, function: NresetFunction) -> Result<(), Error<E>> {
+        self.interface.set_nreset_function(function)
+    }
+
+    /// See [`Sx1509::set_nint_autoclear`](crate::Sx1509::set_nint_autoclear).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_nint_autoclear(&mut self, enabled: bool) -> Result<(), Error<E>> {
+        self.interface.set_nint_autoclear(enabled)
+    }
+
+    /// See [`Sx1509::set_clock_config`](crate::Sx1509::set_clock_config).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_clock_config(&mut self, config: ClockConfig) -> Result<(), Error<E>> {
+        self.interface.set_clock_config(&config)
+    }
+
+    /// See [`Sx1509::clock_config`](crate::Sx1509::clock_config).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn clock_config(&mut self) -> Result<ClockConfig, Error<E>> {
+        self.interface.clock_config()
+    }
+
+    /// See [`Sx1509::led_timing`](crate::Sx1509::led_timing).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn led_timing(&mut self) -> Result<Option<LedTiming>, Error<E>> {
+        Ok(self.interface.led_clock_hz()?.map(LedTiming::new))
+    }
+
+    /// See [`Sx1509::pin_config`](crate::Sx1509::pin_config).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn pin_config<const PIN: u8>(&mut self) -> Result<PinConfig, Error<E>> {
+        const { assert!(PIN < 16) };
+        self.interface.pin_config::<PIN>()
+    }
+
+    /// See [`Sx1509::with_bus`](crate::Sx1509::with_bus). Calling a pin
+    /// method from `f` fails with [`BusBusy`](Error::BusBusy).
+    ///
+    /// # Errors
+    /// This function will return [`BusBusy`](Error::BusBusy) if a pin is
+    /// using the bus.
+    pub fn with_bus<R>(&mut self, f: impl FnOnce(&mut I2C::Bus) -> R) -> Result<R, Error<E>> {
+        self.interface.with_bus(f)
+    }
+
+    /// See [`Sx1509::write_bank_a`](crate::Sx1509::write_bank_a).
+    ///
+    /// ```rust
+    /// # use sx1509_eh::{Pins, Sx1509};
+    /// # struct Failed;
+    /// # impl<E: core::fmt::Debug> From<E> for Failed {
+    /// #     fn from(_: E) -> Self { Failed }
+    /// # }
+    /// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+    /// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+    /// let Pins { a0, a1, a2, a3, a4, a5, a6, a7, mut settings, .. } = expander.split();
+    /// let bar = (a0.into_output()?, a1.into_output()? /* ... */);
+    /// settings.write_bank_a(0b0000_1111)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn write_bank_a(&mut self, data: u8) -> Result<(), Error<E>> {
+        self.interface.write_bank_data(Bank::A, data)
+    }
+
+    /// See [`Sx1509::write_bank_b`](crate::Sx1509::write_bank_b).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn write_bank_b(&mut self, data: u8) -> Result<(), Error<E>> {
+        self.interface.write_bank_data(Bank::B, data)
+    }
+
+    /// See [`Sx1509::write_port`](crate::Sx1509::write_port).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn write_port(&mut self, data: u16) -> Result<(), Error<E>> {
+        self.interface.write_data(data)
+    }
+
+    /// See [`Sx1509::read_register`](crate::Sx1509::read_register).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    #[cfg(feature = "raw")]
+    pub fn read_register(&mut self, register: crate::reg::Register) -> Result<u8, Error<E>> {
+        self.interface.read_register(register)
+    }
+
+    /// See [`Sx1509::write_register`](crate::Sx1509::write_register).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    #[cfg(feature = "raw")]
+    pub fn write_register(
+        &mut self,
+        register: crate::reg::Register,
+        data: u8,
+    ) -> Result<(), Error<E>> {
+        self.interface.write_register(register, data)
+    }
+
+    /// See [`Sx1509::dump_registers`](crate::Sx1509::dump_registers).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn dump_registers(&mut self) -> Result<[u8; REGISTER_COUNT], Error<E>> {
+        self.interface.dump_registers()
+    }
+
+    /// See [`Sx1509::stats`](crate::Sx1509::stats).
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        self.interface.stats()
+    }
+
+    /// See [`Sx1509::reset_stats`](crate::Sx1509::reset_stats).
+    pub fn reset_stats(&mut self) {
+        self.interface.reset_stats();
+    }
+
+    /// See [`Sx1509::read_port`](crate::Sx1509::read_port).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn read_port(&mut self) -> Result<u16, Error<E>> {
+        self.interface.get_data_mask()
+    }
+
+    /// See [`Sx1509::start_batch`](crate::Sx1509::start_batch).
+    pub fn start_batch(&mut self) {
+        self.interface.start_batch();
+    }
+
+    /// See [`Sx1509::flush`](crate::Sx1509::flush).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn flush(&mut self) -> Result<(), Error<E>> {
+        self.interface.flush()
+    }
+}
@@ -1,14 +1,41 @@
 use core::marker::PhantomData;
 
+use embedded_hal::delay::DelayNs;
+
 use crate::{
+    drive::DriveProfile,
     error::{Error, ModeChange},
-    Input, Output,
+    interrupt::Edge,
+    led::Led,
+    lock::BusLock,
+    Input, Interface, Output,
 };
 
+/// The first delay between polls while waiting for an interrupt.
+const WAIT_BACKOFF_MIN_US: u32 = 100;
+/// The longest delay between polls while waiting for an interrupt.
+const WAIT_BACKOFF_MAX_US: u32 = 10_000;
+
 /// A push-pull output.
 pub struct PushPull;
 /// An open-drain output.
 pub struct OpenDrain;
+/// An open-drain output with the internal pull-up enabled.
+pub struct OpenDrainPullUp;
+/// Output states whose internal pulls can be changed freely. This leaves
+/// out [`OpenDrainPullUp`], whose pull-up is part of its state.
+pub trait ManualPulls: sealed::Sealed {}
+
+impl ManualPulls for PushPull {}
+impl ManualPulls for OpenDrain {}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::PushPull {}
+    impl Sealed for super::OpenDrain {}
+}
+
 /// A pull-up input.
 pub struct PullUp;
 /// A pull-down input.
@@ -19,21 +46,64 @@ pub struct Floating;
 pub struct DebounceOn;
 /// A non-debounced input.
 pub struct DebounceOff;
+/// An input that asserts NINT on its sense edge.
+pub struct InterruptOn;
+/// An input that doesn't assert NINT.
+pub struct InterruptOff;
 
-impl<'a, const PIN: u8, I2C, E, S, D> Input<'a, PIN, I2C, S, D>
+impl<'a, const PIN: u8, I2C, E, S, D, N> Input<'a, PIN, I2C, S, D, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
+    /// Invert the pin in hardware, so an active-low signal reads as high when
+    /// it is active. Reads, debouncing and interrupt edges all see the
+    /// inverted level.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_inverted(&mut self, inverted: bool) -> Result<(), Error<E>> {
+        self.interface.set_polarity::<PIN>(inverted)
+    }
+
+    /// Whether the pin is inverted in hardware.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn is_inverted(&mut self) -> Result<bool, Error<E>> {
+        self.interface.get_polarity::<PIN>()
+    }
+
+    /// Let the pin accept logic levels above its bank's supply, up to 5.5V,
+    /// such as from 5V peripherals on a 3.3V board. Only enable this on
+    /// inputs that need it.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_high_input(&mut self, enabled: bool) -> Result<(), Error<E>> {
+        self.interface.set_high_input::<PIN>(enabled)
+    }
+
+    /// Apply the slew, drive strength and high input settings of `profile`
+    /// to the pin in one call. The slew and drive strength only take effect
+    /// once the pin is an output.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_drive_profile(&mut self, profile: DriveProfile) -> Result<(), Error<E>> {
+        self.interface.set_drive_profile(1 << PIN, profile)
+    }
+
     /// Configure the pin as an output.
     ///
     /// # Errors
     /// See [`Pin::into_output`](crate::Pin::into_output).
     pub fn into_output(self) -> Result<Output<'a, PIN, I2C, PushPull>, ModeChange<Error<E>, Self>> {
-        let result = (|| -> Result<(), Error<E>> {
-            self.interface.set_output::<PIN>()?;
-            self.interface.set_open_drain::<PIN>(false)?;
+        let result = self.interface.coalesce(|stage| {
+            stage.disable_interrupt::<PIN>()?;
+            stage.set_output::<PIN>()?;
+            stage.set_open_drain::<PIN>(false)?;
             Ok(())
-        })();
+        });
 
         match result {
             Ok(()) => Ok(Output {
@@ -43,50 +113,226 @@ where
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
+
+    /// Configure the pin to use the LED driver.
+    ///
+    /// # Errors
+    /// See [`Pin::into_led`](crate::Pin::into_led).
+    pub fn into_led(self) -> Result<Led<'a, PIN, I2C>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.disable_interrupt::<PIN>()?;
+            stage.enable_led_driver::<PIN>()?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Led {
+                interface: self.interface,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
 }
 
 impl<'a, const PIN: u8, I2C, E, S> Output<'a, PIN, I2C, S>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
+    /// Configure the pin to use the LED driver.
+    ///
+    /// # Errors
+    /// See [`Pin::into_led`](crate::Pin::into_led).
+    pub fn into_led(self) -> Result<Led<'a, PIN, I2C>, ModeChange<Error<E>, Self>> {
+        match self.interface.enable_led_driver::<PIN>() {
+            Ok(()) => Ok(Led {
+                interface: self.interface,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Flip the output. Once the output has been set, its level is known
+    /// without reading the chip, so this is a single register write. This is
+    /// also what [`StatefulOutputPin::toggle`](embedded_hal::digital::StatefulOutputPin::toggle)
+    /// does.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn toggle(&mut self) -> Result<(), Error<E>> {
+        self.interface.toggle_data::<PIN>()
+    }
+
+    /// Turn the input buffer of the pin off or back on. An output doesn't need
+    /// it, and turning it off saves current when the line floats, such as
+    /// when an open-drain output is released. Reading the output back through
+    /// [`StatefulOutputPin`](embedded_hal::digital::StatefulOutputPin) is
+    /// unreliable while it is off. It is turned back on when the pin becomes
+    /// an input.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_input_buffer(&mut self, enabled: bool) -> Result<(), Error<E>> {
+        self.interface.set_input_disable::<PIN>(!enabled)
+    }
+
+    /// Invert the pin in hardware, so setting an active-low output high
+    /// drives it low. Reading the output back sees the same inverted level,
+    /// so the state stays consistent.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_inverted(&mut self, inverted: bool) -> Result<(), Error<E>> {
+        self.interface.set_polarity::<PIN>(inverted)
+    }
+
+    /// Whether the pin is inverted in hardware.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn is_inverted(&mut self) -> Result<bool, Error<E>> {
+        self.interface.get_polarity::<PIN>()
+    }
+
+    /// Slow down the edges of the pin, or bring them back to full speed. Slow
+    /// edges ring less on long cables and radiate less on EMI-sensitive
+    /// nets.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_long_slew(&mut self, enabled: bool) -> Result<(), Error<E>> {
+        self.interface.set_long_slew::<PIN>(enabled)
+    }
+
+    /// Reduce the drive strength of the pin, or bring it back to full
+    /// strength, to derate outputs that would otherwise source or sink more
+    /// current than needed.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_low_drive(&mut self, enabled: bool) -> Result<(), Error<E>> {
+        self.interface.set_low_drive::<PIN>(enabled)
+    }
+
+    /// Apply the slew, drive strength and high input settings of `profile`
+    /// to the pin in one call, instead of calling
+    /// [`set_long_slew`](Self::set_long_slew),
+    /// [`set_low_drive`](Self::set_low_drive) and
+    /// [`Input::set_high_input`] separately.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn set_drive_profile(&mut self, profile: DriveProfile) -> Result<(), Error<E>> {
+        self.interface.set_drive_profile(1 << PIN, profile)
+    }
+
     /// Configure the pin as an input.
     ///
     /// # Errors
     /// See [`Pin::into_input`](crate::Pin::into_input).
     pub fn into_input(
         self,
-    ) -> Result<Input<'a, PIN, I2C, Floating, DebounceOff>, ModeChange<Error<E>, Self>> {
-        let result = (|| -> Result<(), Error<E>> {
-            self.interface.set_input::<PIN>()?;
-            self.interface.set_pull_up::<PIN>(false)?;
+    ) -> Result<Input<'a, PIN, I2C, Floating, DebounceOff, InterruptOff>, ModeChange<Error<E>, Self>>
+    {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_input::<PIN>()?;
+            stage.set_input_disable::<PIN>(false)?;
+            stage.set_pull_up::<PIN>(false)?;
+            stage.set_pull_down::<PIN>(false)?;
             Ok(())
-        })();
+        });
 
         match result {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
 }
 
-impl<'a, const PIN: u8, I2C, E, D> Input<'a, PIN, I2C, Floating, D>
+impl<const PIN: u8, I2C, E, S> Output<'_, PIN, I2C, S>
+where
+    I2C: BusLock<Error = E>,
+    S: ManualPulls,
+{
+    /// Enable the internal pull-up of the output, and disable its pull-down.
+    /// The pulls work in either direction, so an open-drain output can use it
+    /// instead of an external resistor.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the output can be extracted from the [`ModeChange`],
+    /// although it may be partially configured.
+    pub fn with_pullup(self) -> Result<Self, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_down::<PIN>(false)?;
+            stage.set_pull_up::<PIN>(true)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(self),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Enable the internal pull-down of the output, and disable its pull-up.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the output can be extracted from the [`ModeChange`],
+    /// although it may be partially configured.
+    pub fn with_pulldown(self) -> Result<Self, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_up::<PIN>(false)?;
+            stage.set_pull_down::<PIN>(true)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(self),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Disable both internal pulls of the output.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the output can be extracted from the [`ModeChange`],
+    /// although it may be partially configured.
+    pub fn without_pulls(self) -> Result<Self, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_up::<PIN>(false)?;
+            stage.set_pull_down::<PIN>(false)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(self),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+}
+
+impl<'a, const PIN: u8, I2C, E, D, N> Input<'a, PIN, I2C, Floating, D, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Configure the pin as a pull-up input.
     ///
     /// # Errors
     /// See [`Pin::into_input`](crate::Pin::into_input).
-    pub fn pullup(self) -> Result<Input<'a, PIN, I2C, PullUp, D>, ModeChange<Error<E>, Self>> {
+    pub fn pullup(self) -> Result<Input<'a, PIN, I2C, PullUp, D, N>, ModeChange<Error<E>, Self>> {
         match self.interface.set_pull_up::<PIN>(true) {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
@@ -96,32 +342,38 @@ where
     ///
     /// # Errors
     /// See [`Pin::into_input`](crate::Pin::into_input).
-    pub fn pulldown(self) -> Result<Input<'a, PIN, I2C, PullDown, D>, ModeChange<Error<E>, Self>> {
+    pub fn pulldown(
+        self,
+    ) -> Result<Input<'a, PIN, I2C, PullDown, D, N>, ModeChange<Error<E>, Self>> {
         match self.interface.set_pull_down::<PIN>(true) {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
 }
 
-impl<'a, const PIN: u8, I2C, E, D> Input<'a, PIN, I2C, PullUp, D>
+impl<'a, const PIN: u8, I2C, E, D, N> Input<'a, PIN, I2C, PullUp, D, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Configure the pin as a floating input.
     ///
     /// # Errors
     /// See [`Pin::into_input`](crate::Pin::into_input).
-    pub fn floating(self) -> Result<Input<'a, PIN, I2C, Floating, D>, ModeChange<Error<E>, Self>> {
+    pub fn floating(
+        self,
+    ) -> Result<Input<'a, PIN, I2C, Floating, D, N>, ModeChange<Error<E>, Self>> {
         match self.interface.set_pull_up::<PIN>(false) {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
@@ -131,38 +383,44 @@ where
     ///
     /// # Errors
     /// See [`Pin::into_input`](crate::Pin::into_input).
-    pub fn pulldown(self) -> Result<Input<'a, PIN, I2C, PullDown, D>, ModeChange<Error<E>, Self>> {
-        let result = (|| -> Result<(), Error<E>> {
-            self.interface.set_pull_up::<PIN>(false)?;
-            self.interface.set_pull_down::<PIN>(true)?;
+    pub fn pulldown(
+        self,
+    ) -> Result<Input<'a, PIN, I2C, PullDown, D, N>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_up::<PIN>(false)?;
+            stage.set_pull_down::<PIN>(true)?;
             Ok(())
-        })();
+        });
 
         match result {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
 }
 
-impl<'a, const PIN: u8, I2C, E, D> Input<'a, PIN, I2C, PullDown, D>
+impl<'a, const PIN: u8, I2C, E, D, N> Input<'a, PIN, I2C, PullDown, D, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Configure the pin as a floating input.
     ///
     /// # Errors
     /// See [`Pin::into_input`](crate::Pin::into_input).
-    pub fn floating(self) -> Result<Input<'a, PIN, I2C, Floating, D>, ModeChange<Error<E>, Self>> {
+    pub fn floating(
+        self,
+    ) -> Result<Input<'a, PIN, I2C, Floating, D, N>, ModeChange<Error<E>, Self>> {
         match self.interface.set_pull_down::<PIN>(false) {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
@@ -172,49 +430,54 @@ where
     ///
     /// # Errors
     /// See [`Pin::into_input`](crate::Pin::into_input).
-    pub fn pullup(self) -> Result<Input<'a, PIN, I2C, PullUp, D>, ModeChange<Error<E>, Self>> {
-        let result = (|| -> Result<(), Error<E>> {
-            self.interface.set_pull_down::<PIN>(false)?;
-            self.interface.set_pull_up::<PIN>(true)?;
+    pub fn pullup(self) -> Result<Input<'a, PIN, I2C, PullUp, D, N>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_down::<PIN>(false)?;
+            stage.set_pull_up::<PIN>(true)?;
             Ok(())
-        })();
+        });
 
         match result {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
 }
 
-impl<'a, const PIN: u8, I2C, E, S> Input<'a, PIN, I2C, S, DebounceOff>
+impl<'a, const PIN: u8, I2C, E, S, N> Input<'a, PIN, I2C, S, DebounceOff, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Enable debounce for the pin.
     ///
     /// # Errors
-    /// See [`Pin::into_input`](crate::Pin::into_input).
+    /// This function will return an error if communication with I2C fails, or
+    /// [`OscillatorOff`](Error::OscillatorOff) if the oscillator isn't
+    /// running. If an error occurs, the (unchanged) pin can be extracted from
+    /// the [`ModeChange`].
     pub fn debounce_on(
         self,
-    ) -> Result<Input<'a, PIN, I2C, S, DebounceOn>, ModeChange<Error<E>, Self>> {
+    ) -> Result<Input<'a, PIN, I2C, S, DebounceOn, N>, ModeChange<Error<E>, Self>> {
         match self.interface.set_debounce_enable::<PIN>(true) {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
 }
 
-impl<'a, const PIN: u8, I2C, E, S> Input<'a, PIN, I2C, S, DebounceOn>
+impl<'a, const PIN: u8, I2C, E, S, N> Input<'a, PIN, I2C, S, DebounceOn, N>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Disable debounce for the pin.
     ///
@@ -222,12 +485,13 @@ where
     /// See [`Pin::into_input`](crate::Pin::into_input).
     pub fn debounce_off(
         self,
-    ) -> Result<Input<'a, PIN, I2C, S, DebounceOff>, ModeChange<Error<E>, Self>> {
+    ) -> Result<Input<'a, PIN, I2C, S, DebounceOff, N>, ModeChange<Error<E>, Self>> {
         match self.interface.set_debounce_enable::<PIN>(false) {
             Ok(()) => Ok(Input {
                 interface: self.interface,
                 _state: PhantomData,
                 _debounce: PhantomData,
+                _interrupt: PhantomData,
             }),
             Err(error) => Err(ModeChange { error, pin: self }),
         }
@@ -236,7 +500,7 @@ where
 
 impl<'a, const PIN: u8, I2C, E> Output<'a, PIN, I2C, PushPull>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Configure the pin as an open-drain output.
     ///
@@ -251,11 +515,37 @@ where
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
+
+    /// Configure the pin as an open-drain output with the internal pull-up,
+    /// the usual way to drive a shared active-low line.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the output can be extracted from the [`ModeChange`],
+    /// although it may be partially configured.
+    pub fn open_drain_pullup(
+        self,
+    ) -> Result<Output<'a, PIN, I2C, OpenDrainPullUp>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_down::<PIN>(false)?;
+            stage.set_pull_up::<PIN>(true)?;
+            stage.set_open_drain::<PIN>(true)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Output {
+                interface: self.interface,
+                _state: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
 }
 
 impl<'a, const PIN: u8, I2C, E> Output<'a, PIN, I2C, OpenDrain>
 where
-    I2C: embedded_hal::i2c::I2c<Error = E>,
+    I2C: BusLock<Error = E>,
 {
     /// Configure the pin as a push-pull output.
     ///
@@ -270,4 +560,194 @@ where
             Err(error) => Err(ModeChange { error, pin: self }),
         }
     }
+
+    /// Configure the pin as an open-drain output with the internal pull-up,
+    /// the usual way to drive a shared active-low line.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the output can be extracted from the [`ModeChange`],
+    /// although it may be partially configured.
+    pub fn open_drain_pullup(
+        self,
+    ) -> Result<Output<'a, PIN, I2C, OpenDrainPullUp>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_down::<PIN>(false)?;
+            stage.set_pull_up::<PIN>(true)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Output {
+                interface: self.interface,
+                _state: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+}
+
+impl<'a, const PIN: u8, I2C, E> Output<'a, PIN, I2C, OpenDrainPullUp>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Configure the pin as an open-drain output without the pull-up.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the output can be extracted from the [`ModeChange`],
+    /// although it may be partially configured.
+    pub fn open_drain(self) -> Result<Output<'a, PIN, I2C, OpenDrain>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_up::<PIN>(false)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Output {
+                interface: self.interface,
+                _state: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Configure the pin as a push-pull output without the pull-up.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails. If
+    /// an error occurs, the output can be extracted from the [`ModeChange`],
+    /// although it may be partially configured.
+    pub fn push_pull(self) -> Result<Output<'a, PIN, I2C, PushPull>, ModeChange<Error<E>, Self>> {
+        let result = self.interface.coalesce(|stage| {
+            stage.set_pull_up::<PIN>(false)?;
+            stage.set_open_drain::<PIN>(false)?;
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => Ok(Output {
+                interface: self.interface,
+                _state: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+}
+
+impl<'a, const PIN: u8, I2C, E, S, D> Input<'a, PIN, I2C, S, D, InterruptOff>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Make the pin assert NINT when `edge` is seen, by setting its edge
+    /// sensitivity and unmasking it as an interrupt source.
+    ///
+    /// # Errors
+    /// See [`Pin::into_input`](crate::Pin::into_input).
+    pub fn enable_interrupt(
+        self,
+        edge: Edge,
+    ) -> Result<Input<'a, PIN, I2C, S, D, InterruptOn>, ModeChange<Error<E>, Self>> {
+        match self.interface.enable_interrupt::<PIN>(edge) {
+            Ok(()) => Ok(Input {
+                interface: self.interface,
+                _state: PhantomData,
+                _debounce: PhantomData,
+                _interrupt: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Block until `edge` is seen on the pin. This sets up the pin's edge
+    /// sensitivity, then polls its interrupt source bit, backing off from
+    /// 100µs up to 10ms between polls to keep bus traffic down. The event is
+    /// cleared, and the pin masked and its previous edge sensitivity restored
+    /// before returning, even on an error, so NINT is only asserted while
+    /// waiting.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn wait_for_edge<DELAY: DelayNs>(
+        &mut self,
+        edge: Edge,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<E>> {
+        let previous = self.interface.interrupt_edge::<PIN>()?;
+        self.interface.clear_interrupts(1 << PIN)?;
+        let result = self
+            .interface
+            .enable_interrupt::<PIN>(edge)
+            .and_then(|()| wait_for_interrupt::<PIN, _, _>(self.interface, delay));
+
+        // Leave the pin as its `InterruptOff` state says, whatever happened.
+        let restored = self.interface.coalesce(|stage| {
+            stage.disable_interrupt::<PIN>()?;
+            stage.set_sense::<PIN>(previous)?;
+            Ok(())
+        });
+        result.and(restored)
+    }
+}
+
+impl<'a, const PIN: u8, I2C, E, S, D> Input<'a, PIN, I2C, S, D, InterruptOn>
+where
+    I2C: BusLock<Error = E>,
+{
+    /// Mask the pin so that it no longer asserts NINT.
+    ///
+    /// # Errors
+    /// See [`Pin::into_input`](crate::Pin::into_input).
+    pub fn disable_interrupt(
+        self,
+    ) -> Result<Input<'a, PIN, I2C, S, D, InterruptOff>, ModeChange<Error<E>, Self>> {
+        match self.interface.disable_interrupt::<PIN>() {
+            Ok(()) => Ok(Input {
+                interface: self.interface,
+                _state: PhantomData,
+                _debounce: PhantomData,
+                _interrupt: PhantomData,
+            }),
+            Err(error) => Err(ModeChange { error, pin: self }),
+        }
+    }
+
+    /// Clear the pin's interrupt and latched event bits. See
+    /// [`Sx1509::clear_interrupts`](crate::Sx1509::clear_interrupts).
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn clear_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.interface.clear_interrupts(1 << PIN)
+    }
+
+    /// Block until the pin triggers an interrupt, polling like
+    /// [`wait_for_edge`](Input::wait_for_edge), then clear it.
+    ///
+    /// # Errors
+    /// This function will return an error if communication with I2C fails.
+    pub fn wait_for_interrupt<DELAY: DelayNs>(
+        &mut self,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<E>> {
+        wait_for_interrupt::<PIN, _, _>(self.interface, delay)
+    }
+}
+
+/// Poll the interrupt source bit of `PIN` with an increasing delay until it
+/// is set, then clear it.
+fn wait_for_interrupt<const PIN: u8, I2C, E>(
+    interface: &Interface<I2C>,
+    delay: &mut impl DelayNs,
+) -> Result<(), Error<E>>
+where
+    I2C: BusLock<Error = E>,
+{
+    let mut backoff = WAIT_BACKOFF_MIN_US;
+    while interface.interrupt_source()? & (1 << PIN) == 0 {
+        delay.delay_us(backoff);
+        backoff = (backoff * 2).min(WAIT_BACKOFF_MAX_US);
+    }
+
+    interface.clear_interrupts(1 << PIN)
 }
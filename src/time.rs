@@ -0,0 +1,48 @@
+use core::time::Duration;
+
+/// A duration accepted by the timing APIs, such as
+/// [`Sx1509::set_debounce_duration`](crate::Sx1509::set_debounce_duration)
+/// and [`LedTiming`](crate::led::LedTiming). It is implemented for
+/// [`core::time::Duration`], and for `fugit` durations with the `fugit`
+/// feature.
+///
+/// ```rust
+/// # use sx1509_eh::Sx1509;
+/// # struct Failed;
+/// # impl<E: core::fmt::Debug> From<E> for Failed {
+/// #     fn from(_: E) -> Self { Failed }
+/// # }
+/// # #[cfg(feature = "fugit")]
+/// # fn example<I2C: embedded_hal::i2c::I2c>(i2c: I2C) -> Result<(), Failed> {
+/// # let mut expander = Sx1509::new(i2c, 0x3E)?;
+/// use fugit::MillisDurationU32;
+///
+/// expander.set_debounce_duration(MillisDurationU32::millis(8))?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait IntoDuration {
+    /// Convert into a [`Duration`].
+    fn into_duration(self) -> Duration;
+}
+
+impl IntoDuration for Duration {
+    fn into_duration(self) -> Duration {
+        self
+    }
+}
+
+#[cfg(feature = "fugit")]
+impl<const NOM: u32, const DENOM: u32> IntoDuration for fugit::Duration<u32, NOM, DENOM> {
+    fn into_duration(self) -> Duration {
+        fugit::Duration::<u64, NOM, DENOM>::from_ticks(u64::from(self.ticks())).into_duration()
+    }
+}
+
+#[cfg(feature = "fugit")]
+impl<const NOM: u32, const DENOM: u32> IntoDuration for fugit::Duration<u64, NOM, DENOM> {
+    fn into_duration(self) -> Duration {
+        let nanos = u128::from(self.ticks()) * u128::from(NOM) * 1_000_000_000 / u128::from(DENOM);
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+}